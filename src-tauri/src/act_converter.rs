@@ -1,20 +1,104 @@
 use std::fs::{self, File};
-use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::io::{self, BufWriter, Write};
 use std::path::{Path, PathBuf};
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use crate::act_file::{self, ActFile};
+
+/// A field that the target version has no room for, with the non-zero value it held.
+pub struct DroppedField {
+    pub action: String,
+    /// Position of the signal within the action, for signal fields.
+    pub signal: Option<usize>,
+    pub field: &'static str,
+    pub value: u32,
+}
+
+/// Non-zero fields of `act` that would be lost by writing it at `target_version`.
+pub fn dropped_fields(act: &ActFile, target_version: u32) -> Vec<DroppedField> {
+    let mut dropped = Vec::new();
+    if target_version >= act.version {
+        return dropped;
+    }
 
-pub fn convert_act_v6_to_v5(input_file: &str, output_base: &str) -> io::Result<bool> {
+    for action in &act.actions {
+        let action_fields = [
+            (2, "unk_v2", action.unk_v2),
+            (3, "unk_v3", action.unk_v3),
+            (4, "unk_v4", action.unk_v4 as u32),
+            (5, "unk_v5", action.unk_v5),
+            (6, "unk_v6", action.unk_v6 as u32),
+        ];
+        for (since, field, value) in action_fields {
+            if act.version >= since && target_version < since && value != 0 {
+                dropped.push(DroppedField { action: action.name.to_string(), signal: None, field, value });
+            }
+        }
+
+        if act.version >= 6 && target_version < 6 {
+            for (index, signal) in action.signals.iter().enumerate() {
+                if signal.act6_signal != 0 {
+                    dropped.push(DroppedField {
+                        action: action.name.to_string(),
+                        signal: Some(index),
+                        field: "act6_signal",
+                        value: signal.act6_signal as u32,
+                    });
+                }
+            }
+        }
+    }
+    dropped
+}
+
+/// Lists dropped fields one per line, naming the action and, for signal fields, the signal.
+pub fn write_dropped_report(dropped: &[DroppedField], report_file: &Path) -> io::Result<()> {
+    let mut output = BufWriter::new(File::create(report_file)?);
+    for field in dropped {
+        match field.signal {
+            Some(signal) => writeln!(output, "action {}, signal {}: {} = {}", field.action, signal, field.field, field.value)?,
+            None => writeln!(output, "action {}: {} = {}", field.action, field.field, field.value)?,
+        }
+    }
+    output.flush()
+}
+
+/// Writes the `.dropped.txt` report next to `output_file`, or removes a stale one when nothing was dropped.
+pub fn update_dropped_report(dropped: &[DroppedField], output_file: &Path) -> io::Result<()> {
+    let report_path = output_file.with_extension("dropped.txt");
+    if !dropped.is_empty() {
+        write_dropped_report(dropped, &report_path)
+    } else if report_path.exists() {
+        // Don't leave a report from an earlier run next to a clean conversion.
+        fs::remove_file(&report_path)
+    } else {
+        Ok(())
+    }
+}
+
+/// Rewrites a .act at `target_version`. Fields added by an upgrade are zero; fields removed by a
+/// downgrade are returned and listed in a `.dropped.txt` report when they held anything other than zero.
+pub fn convert_act(input_file: &str, output_file: &str, target_version: u32) -> io::Result<Vec<DroppedField>> {
+    if !(1..=act_file::LATEST_VERSION).contains(&target_version) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Unsupported act version {}", target_version)));
+    }
+
+    let mut act = act_file::read_act(input_file)?;
+    let dropped = dropped_fields(&act, target_version);
+    act.version = target_version;
+    act_file::write_act(&act, output_file)?;
+    update_dropped_report(&dropped, Path::new(output_file))?;
+
+    Ok(dropped)
+}
+
+/// Converts a v6 .act to v5, returning `None` for files that are not v6. Non-zero v6 fields that
+/// had to be dropped are returned and listed in a `.dropped.txt` report next to the output.
+pub fn convert_act_v6_to_v5(input_file: &str, output_base: &str) -> io::Result<Option<Vec<DroppedField>>> {
     let input_path = Path::new(input_file);
     let file_name = input_path.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid file name"))?;
 
-    let file = File::open(input_file)?;
-    let mut fs = BufReader::new(file);
-
-    let mut header = [0u8; 32];
-    fs.read_exact(&mut header)?;
-    let version = fs.read_u32::<LittleEndian>()?;
-    if version < 6 {
-        return Ok(false);
+    let mut act = act_file::read_act(input_file)?;
+    if act.version < 6 {
+        return Ok(None);
     }
 
     let parent = input_path.parent().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid parent path"))?;
@@ -39,115 +123,10 @@ pub fn convert_act_v6_to_v5(input_file: &str, output_base: &str) -> io::Result<b
     output_path.push(file_name);
     output_path.set_extension("act");
 
-    let action_count = fs.read_u32::<LittleEndian>()?;
-
-    let mut output = BufWriter::new(File::create(&output_path)?);
+    let dropped = dropped_fields(&act, 5);
+    act.version = 5;
+    act_file::write_act(&act, output_path.to_str().unwrap())?;
+    update_dropped_report(&dropped, &output_path)?;
 
-    output.write_all(&header)?;
-    output.write_u32::<LittleEndian>(5)?;
-    output.write_u32::<LittleEndian>(action_count)?;
-
-    for _ in 0..action_count {
-        let _name = read_string(&mut fs, &mut output)?;
-        let _link_ani_name = read_string(&mut fs, &mut output)?;
-        let dw_length = fs.read_u32::<LittleEndian>()?;
-        output.write_u32::<LittleEndian>(dw_length)?;
-        let _next_action_name = read_string(&mut fs, &mut output)?;
-        let dw_blend_frame = fs.read_u32::<LittleEndian>()?;
-        output.write_u32::<LittleEndian>(dw_blend_frame)?;
-        let dw_next_action_frame = fs.read_u32::<LittleEndian>()?;
-        output.write_u32::<LittleEndian>(dw_next_action_frame)?;
-
-        if version >= 2 {
-            let unk_v2 = fs.read_u32::<LittleEndian>()?;
-            output.write_u32::<LittleEndian>(unk_v2)?;
-        }
-        if version >= 3 {
-            let unk_v3 = fs.read_u32::<LittleEndian>()?;
-            output.write_u32::<LittleEndian>(unk_v3)?;
-        }
-        if version >= 4 {
-            let unk_v4 = fs.read_u8()?;
-            output.write_u8(unk_v4)?;
-        }
-        if version >= 5 {
-            let unk_v5 = fs.read_u32::<LittleEndian>()?;
-            output.write_u32::<LittleEndian>(unk_v5)?;
-        }
-        if version >= 6 {
-            let _unk_v6 = fs.read_u8()?;
-        }
-
-        let signal_count = fs.read_u32::<LittleEndian>()?;
-        output.write_u32::<LittleEndian>(signal_count)?;
-        
-        for _ in 0..signal_count {
-            read_multiple_u32(&mut fs, &mut output, 4)?;
-            let mut m_p_data = [0u8; 256];
-            fs.read_exact(&mut m_p_data)?;
-            output.write_all(&m_p_data)?;
-
-            read_table(&mut fs, &mut output, 2)?;
-            read_table(&mut fs, &mut output, 3)?;
-            read_table(&mut fs, &mut output, 4)?;
-            read_variable_size_table(&mut fs, &mut output)?;
-
-            if version >= 6 {
-                let _act6_signal = fs.read_u8()?; 
-            }
-        }
-    }
-    
-    Ok(true)
-}
-
-fn read_string<R: Read, W: Write>(reader: &mut R, writer: &mut W) -> io::Result<String> {
-    let len = reader.read_u32::<LittleEndian>()?;
-    writer.write_u32::<LittleEndian>(len)?;
-    let mut buf = vec![0u8; len as usize];
-    reader.read_exact(&mut buf)?;
-    writer.write_all(&buf)?;
-    Ok(String::from_utf8_lossy(&buf).to_string())
-}
-
-fn read_multiple_u32<R: Read, W: Write>(reader: &mut R, writer: &mut W, count: usize) -> io::Result<()> {
-    let mut buf = vec![0u8; count * 4];
-    reader.read_exact(&mut buf)?;
-    writer.write_all(&buf)?;
-    Ok(())
-}
-
-fn read_table<R: Read, W: Write>(reader: &mut R, writer: &mut W, float_count: usize) -> io::Result<()> {
-    let n_table_count = reader.read_u32::<LittleEndian>()?;
-    writer.write_u32::<LittleEndian>(n_table_count)?;
-    if n_table_count > 0 {
-        read_multiple_u32(reader, writer, n_table_count as usize)?;
-        for _ in 0..n_table_count {
-            for _ in 0..float_count {
-                let val = reader.read_f32::<LittleEndian>()?;
-                writer.write_f32::<LittleEndian>(val)?;
-            }
-        }
-    }
-    Ok(())
-}
-
-fn read_variable_size_table<R: Read, W: Write>(reader: &mut R, writer: &mut W) -> io::Result<()> {
-    let n_table_count = reader.read_u32::<LittleEndian>()?;
-    writer.write_u32::<LittleEndian>(n_table_count)?;
-    if n_table_count > 0 {
-        read_multiple_u32(reader, writer, n_table_count as usize)?;
-        for _ in 0..n_table_count {
-            let size = reader.read_u32::<LittleEndian>()?;
-            writer.write_u32::<LittleEndian>(size)?;
-            if let Ok(size_usize) = usize::try_from(size) {
-                let mut data = vec![0; size_usize];
-                reader.read_exact(&mut data)?;
-                writer.write_all(&data)?;
-            } else {
-                return Err(io::Error::new(io::ErrorKind::InvalidData, "Size too large"));
-            }
-        }
-    }
-    Ok(())
+    Ok(Some(dropped))
 }
@@ -0,0 +1,69 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use crate::act_converter;
+use crate::act_file::{self, ActFile};
+use crate::act_signal::{self, SignalDefinitions};
+
+fn is_yaml(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    lower.ends_with(".yaml") || lower.ends_with(".yml")
+}
+
+fn invalid(path: &str, message: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, message))
+}
+
+/// Writes a .act as YAML (for .yaml/.yml outputs) or JSON, decoding signal data when definitions
+/// are given; returns the number of actions.
+pub fn export_act(input_file: &str, output_file: &str, signals: Option<&SignalDefinitions>) -> io::Result<usize> {
+    let mut act = act_file::read_act(input_file)?;
+    if let Some(signals) = signals {
+        act_signal::decode_signals(&mut act, signals);
+    }
+
+    let mut output = BufWriter::new(File::create(output_file)?);
+    if is_yaml(output_file) {
+        serde_yaml::to_writer(&mut output, &act).map_err(|e| invalid(output_file, e))?;
+    } else {
+        serde_json::to_writer_pretty(&mut output, &act)?;
+    }
+    output.flush()?;
+
+    Ok(act.actions.len())
+}
+
+/// Compiles a JSON/YAML export back into a .act, at `version` if given and at the exported version otherwise.
+/// Decoded signal fields override the matching bytes of the hex data block. Non-zero fields that a lower
+/// version has no room for are listed in a `.dropped.txt` report next to the output.
+pub fn import_act(input_file: &str, output_file: &str, version: Option<u32>, signals: Option<&SignalDefinitions>) -> io::Result<usize> {
+    let text = fs::read_to_string(input_file)?;
+    let mut act: ActFile = if is_yaml(input_file) {
+        serde_yaml::from_str(&text).map_err(|e| invalid(input_file, e))?
+    } else {
+        serde_json::from_str(&text).map_err(|e| invalid(input_file, e))?
+    };
+    let mut dropped = Vec::new();
+    if let Some(version) = version {
+        dropped = act_converter::dropped_fields(&act, version);
+        act.version = version;
+    }
+    if !(1..=act_file::LATEST_VERSION).contains(&act.version) {
+        return Err(invalid(input_file, format!("unsupported act version {}", act.version)));
+    }
+    match signals {
+        Some(signals) => act_signal::encode_signals(&mut act, signals).map_err(|e| invalid(input_file, e))?,
+        None if act.actions.iter().flat_map(|a| &a.signals).any(|s| !s.fields.is_empty()) => {
+            return Err(invalid(input_file, "decoded signal fields need a signal definition file"));
+        }
+        None => {}
+    }
+
+    // Serialize first so a bad signal block does not leave a truncated .act behind.
+    let mut bytes = Vec::new();
+    act_file::write_act_to(&act, &mut bytes)?;
+    fs::write(output_file, bytes)?;
+    act_converter::update_dropped_report(&dropped, Path::new(output_file))?;
+
+    Ok(act.actions.len())
+}
@@ -0,0 +1,553 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// Newest layout this module reads and writes.
+pub const LATEST_VERSION: u32 = 6;
+pub const HEADER_SIZE: usize = 32;
+pub const SIGNAL_DATA_SIZE: usize = 256;
+
+#[derive(Serialize, Deserialize)]
+pub struct ActFile {
+    #[serde(with = "hex_bytes")]
+    pub header: [u8; HEADER_SIZE],
+    pub version: u32,
+    pub actions: Vec<Action>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Action {
+    pub name: ActString,
+    pub link_ani_name: ActString,
+    pub dw_length: u32,
+    pub next_action_name: ActString,
+    pub blend_frame: u32,
+    pub next_action_frame: u32,
+    /// Present from version 2.
+    #[serde(default)]
+    pub unk_v2: u32,
+    /// Present from version 3.
+    #[serde(default)]
+    pub unk_v3: u32,
+    /// Present from version 4.
+    #[serde(default)]
+    pub unk_v4: u8,
+    /// Present from version 5.
+    #[serde(default)]
+    pub unk_v5: u32,
+    /// Present from version 6.
+    #[serde(default)]
+    pub unk_v6: u8,
+    pub signals: Vec<Signal>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Signal {
+    pub signal_index: u32,
+    pub start_frame: u32,
+    pub end_frame: u32,
+    pub y_order: u32,
+    /// Raw `m_p_data` block, always `SIGNAL_DATA_SIZE` bytes.
+    #[serde(with = "hex_bytes")]
+    pub data: Vec<u8>,
+    pub vec2_table: Vec<VectorEntry>,
+    pub vec3_table: Vec<VectorEntry>,
+    pub vec4_table: Vec<VectorEntry>,
+    pub string_table: Vec<StringEntry>,
+    /// Present from version 6.
+    #[serde(default)]
+    pub act6_signal: u8,
+    /// Name from the signal definitions; only used in text exports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal_name: Option<String>,
+    /// Data block fields decoded with the signal definitions; only used in text exports.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub fields: IndexMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VectorEntry {
+    pub index: u32,
+    pub values: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StringEntry {
+    pub index: u32,
+    #[serde(with = "hex_bytes")]
+    pub data: Vec<u8>,
+}
+
+/// A name stored in an act file. The bytes are kept as they are so names that are not UTF-8 are
+/// written back unchanged; text exports show them as `{ hex: ... }` instead of a string.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct ActString(pub Vec<u8>);
+
+impl ActString {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for ActString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.0))
+    }
+}
+
+impl From<&str> for ActString {
+    fn from(text: &str) -> Self {
+        ActString(text.as_bytes().to_vec())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ActStringText {
+    Text(String),
+    Hex { hex: String },
+}
+
+impl Serialize for ActString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match std::str::from_utf8(&self.0) {
+            Ok(text) => ActStringText::Text(text.to_string()),
+            Err(_) => ActStringText::Hex { hex: hex_bytes::encode(&self.0) },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ActString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match ActStringText::deserialize(deserializer)? {
+            ActStringText::Text(text) => Ok(ActString(text.into_bytes())),
+            ActStringText::Hex { hex } => hex_bytes::decode(&hex).map(ActString).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// Raw byte blocks are written as hex strings in text exports.
+mod hex_bytes {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn encode(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    pub fn decode(text: &str) -> Result<Vec<u8>, String> {
+        let text: String = text.split_whitespace().collect();
+        if text.len() % 2 != 0 {
+            return Err(format!("odd number of hex digits ({})", text.len()));
+        }
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|e| format!("invalid hex data: {}", e))
+    }
+
+    pub fn serialize<S: Serializer>(bytes: impl AsRef<[u8]>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode(bytes.as_ref()))
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<Vec<u8>>,
+    {
+        let bytes = decode(&String::deserialize(deserializer)?).map_err(D::Error::custom)?;
+        let length = bytes.len();
+        T::try_from(bytes).map_err(|_| D::Error::custom(format!("unexpected byte count {}", length)))
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Reads `len` bytes through `take`, so a corrupt length fails on the short read instead of on the allocation.
+fn read_bytes<R: Read>(reader: &mut R, len: u32) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    reader.take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() != len as usize {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("act file ends {} bytes into a {}-byte block", buf.len(), len)));
+    }
+    Ok(buf)
+}
+
+fn read_string<R: Read>(reader: &mut R) -> io::Result<ActString> {
+    let len = reader.read_u32::<LittleEndian>()?;
+    read_bytes(reader, len).map(ActString)
+}
+
+fn write_string<W: Write>(writer: &mut W, text: &ActString) -> io::Result<()> {
+    writer.write_u32::<LittleEndian>(text.0.len() as u32)?;
+    writer.write_all(&text.0)
+}
+
+/// Reads a table of `count` indices followed by `count` vectors of `float_count` floats.
+fn read_vector_table<R: Read>(reader: &mut R, float_count: usize) -> io::Result<Vec<VectorEntry>> {
+    let count = reader.read_u32::<LittleEndian>()? as usize;
+    let mut indices = Vec::new();
+    for _ in 0..count {
+        indices.push(reader.read_u32::<LittleEndian>()?);
+    }
+
+    let mut entries = Vec::new();
+    for index in indices {
+        let mut values = Vec::with_capacity(float_count);
+        for _ in 0..float_count {
+            values.push(reader.read_f32::<LittleEndian>()?);
+        }
+        entries.push(VectorEntry { index, values });
+    }
+    Ok(entries)
+}
+
+fn write_vector_table<W: Write>(writer: &mut W, entries: &[VectorEntry], float_count: usize) -> io::Result<()> {
+    writer.write_u32::<LittleEndian>(entries.len() as u32)?;
+    for entry in entries {
+        writer.write_u32::<LittleEndian>(entry.index)?;
+    }
+    for entry in entries {
+        if entry.values.len() != float_count {
+            return Err(invalid(format!(
+                "Table entry {} has {} values, expected {}", entry.index, entry.values.len(), float_count
+            )));
+        }
+        for value in &entry.values {
+            writer.write_f32::<LittleEndian>(*value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a table of `count` indices followed by `count` length-prefixed byte strings.
+fn read_string_table<R: Read>(reader: &mut R) -> io::Result<Vec<StringEntry>> {
+    let count = reader.read_u32::<LittleEndian>()? as usize;
+    let mut indices = Vec::new();
+    for _ in 0..count {
+        indices.push(reader.read_u32::<LittleEndian>()?);
+    }
+
+    let mut entries = Vec::new();
+    for index in indices {
+        let size = reader.read_u32::<LittleEndian>()?;
+        let data = read_bytes(reader, size)?;
+        entries.push(StringEntry { index, data });
+    }
+    Ok(entries)
+}
+
+fn write_string_table<W: Write>(writer: &mut W, entries: &[StringEntry]) -> io::Result<()> {
+    writer.write_u32::<LittleEndian>(entries.len() as u32)?;
+    for entry in entries {
+        writer.write_u32::<LittleEndian>(entry.index)?;
+    }
+    for entry in entries {
+        writer.write_u32::<LittleEndian>(entry.data.len() as u32)?;
+        writer.write_all(&entry.data)?;
+    }
+    Ok(())
+}
+
+fn read_signal<R: Read>(reader: &mut R, version: u32) -> io::Result<Signal> {
+    let signal_index = reader.read_u32::<LittleEndian>()?;
+    let start_frame = reader.read_u32::<LittleEndian>()?;
+    let end_frame = reader.read_u32::<LittleEndian>()?;
+    let y_order = reader.read_u32::<LittleEndian>()?;
+    let mut data = vec![0u8; SIGNAL_DATA_SIZE];
+    reader.read_exact(&mut data)?;
+
+    let vec2_table = read_vector_table(reader, 2)?;
+    let vec3_table = read_vector_table(reader, 3)?;
+    let vec4_table = read_vector_table(reader, 4)?;
+    let string_table = read_string_table(reader)?;
+    let act6_signal = if version >= 6 { reader.read_u8()? } else { 0 };
+
+    Ok(Signal {
+        signal_index, start_frame, end_frame, y_order, data,
+        vec2_table, vec3_table, vec4_table, string_table, act6_signal,
+        signal_name: None,
+        fields: IndexMap::new(),
+    })
+}
+
+fn write_signal<W: Write>(writer: &mut W, signal: &Signal, version: u32) -> io::Result<()> {
+    if signal.data.len() != SIGNAL_DATA_SIZE {
+        return Err(invalid(format!("Signal data is {} bytes, expected {}", signal.data.len(), SIGNAL_DATA_SIZE)));
+    }
+    writer.write_u32::<LittleEndian>(signal.signal_index)?;
+    writer.write_u32::<LittleEndian>(signal.start_frame)?;
+    writer.write_u32::<LittleEndian>(signal.end_frame)?;
+    writer.write_u32::<LittleEndian>(signal.y_order)?;
+    writer.write_all(&signal.data)?;
+
+    write_vector_table(writer, &signal.vec2_table, 2)?;
+    write_vector_table(writer, &signal.vec3_table, 3)?;
+    write_vector_table(writer, &signal.vec4_table, 4)?;
+    write_string_table(writer, &signal.string_table)?;
+    if version >= 6 {
+        writer.write_u8(signal.act6_signal)?;
+    }
+    Ok(())
+}
+
+fn read_action<R: Read>(reader: &mut R, version: u32) -> io::Result<Action> {
+    let name = read_string(reader)?;
+    let link_ani_name = read_string(reader)?;
+    let dw_length = reader.read_u32::<LittleEndian>()?;
+    let next_action_name = read_string(reader)?;
+    let blend_frame = reader.read_u32::<LittleEndian>()?;
+    let next_action_frame = reader.read_u32::<LittleEndian>()?;
+
+    let unk_v2 = if version >= 2 { reader.read_u32::<LittleEndian>()? } else { 0 };
+    let unk_v3 = if version >= 3 { reader.read_u32::<LittleEndian>()? } else { 0 };
+    let unk_v4 = if version >= 4 { reader.read_u8()? } else { 0 };
+    let unk_v5 = if version >= 5 { reader.read_u32::<LittleEndian>()? } else { 0 };
+    let unk_v6 = if version >= 6 { reader.read_u8()? } else { 0 };
+
+    let signal_count = reader.read_u32::<LittleEndian>()?;
+    let mut signals = Vec::new();
+    for _ in 0..signal_count {
+        signals.push(read_signal(reader, version)?);
+    }
+
+    Ok(Action {
+        name, link_ani_name, dw_length, next_action_name, blend_frame, next_action_frame,
+        unk_v2, unk_v3, unk_v4, unk_v5, unk_v6, signals,
+    })
+}
+
+fn write_action<W: Write>(writer: &mut W, action: &Action, version: u32) -> io::Result<()> {
+    write_string(writer, &action.name)?;
+    write_string(writer, &action.link_ani_name)?;
+    writer.write_u32::<LittleEndian>(action.dw_length)?;
+    write_string(writer, &action.next_action_name)?;
+    writer.write_u32::<LittleEndian>(action.blend_frame)?;
+    writer.write_u32::<LittleEndian>(action.next_action_frame)?;
+
+    if version >= 2 {
+        writer.write_u32::<LittleEndian>(action.unk_v2)?;
+    }
+    if version >= 3 {
+        writer.write_u32::<LittleEndian>(action.unk_v3)?;
+    }
+    if version >= 4 {
+        writer.write_u8(action.unk_v4)?;
+    }
+    if version >= 5 {
+        writer.write_u32::<LittleEndian>(action.unk_v5)?;
+    }
+    if version >= 6 {
+        writer.write_u8(action.unk_v6)?;
+    }
+
+    writer.write_u32::<LittleEndian>(action.signals.len() as u32)?;
+    for signal in &action.signals {
+        write_signal(writer, signal, version)?;
+    }
+    Ok(())
+}
+
+pub fn parse_act<R: Read>(reader: &mut R) -> io::Result<ActFile> {
+    let mut header = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut header)?;
+    let version = reader.read_u32::<LittleEndian>()?;
+    if !(1..=LATEST_VERSION).contains(&version) {
+        return Err(invalid(format!("Unsupported act version {}", version)));
+    }
+
+    let action_count = reader.read_u32::<LittleEndian>()?;
+    let mut actions = Vec::new();
+    for _ in 0..action_count {
+        actions.push(read_action(reader, version)?);
+    }
+
+    Ok(ActFile { header, version, actions })
+}
+
+/// Writes the file in the layout of `act.version`; fields newer than that version are left out.
+pub fn write_act_to<W: Write>(act: &ActFile, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&act.header)?;
+    writer.write_u32::<LittleEndian>(act.version)?;
+    writer.write_u32::<LittleEndian>(act.actions.len() as u32)?;
+    for action in &act.actions {
+        write_action(writer, action, act.version)?;
+    }
+    Ok(())
+}
+
+pub fn read_act(path: &str) -> io::Result<ActFile> {
+    parse_act(&mut BufReader::new(File::open(path)?))
+}
+
+pub fn write_act(act: &ActFile, path: &str) -> io::Result<()> {
+    let mut output = BufWriter::new(File::create(path)?);
+    write_act_to(act, &mut output)?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_string(out: &mut Vec<u8>, bytes: &[u8]) {
+        out.write_u32::<LittleEndian>(bytes.len() as u32).unwrap();
+        out.extend_from_slice(bytes);
+    }
+
+    /// A file laid out by hand for `version`: one action without signals, then one whose signal
+    /// fills every table. Every version-gated field holds a distinct non-zero value.
+    fn fixture(version: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend((0..HEADER_SIZE as u8).map(|b| b ^ 0xa5));
+        out.write_u32::<LittleEndian>(version).unwrap();
+        out.write_u32::<LittleEndian>(2).unwrap();
+
+        for (action, name) in [(0u8, &b"Stand"[..]), (1, &b"\xb0\xf8\xb0\xdd"[..])] {
+            put_string(&mut out, name);
+            put_string(&mut out, b"ani_stand");
+            out.write_u32::<LittleEndian>(30 + action as u32).unwrap();
+            put_string(&mut out, if action == 0 { &b""[..] } else { &b"Stand"[..] });
+            out.write_u32::<LittleEndian>(3).unwrap();
+            out.write_u32::<LittleEndian>(4).unwrap();
+            if version >= 2 { out.write_u32::<LittleEndian>(0x0202_0202).unwrap(); }
+            if version >= 3 { out.write_u32::<LittleEndian>(0x0303_0303).unwrap(); }
+            if version >= 4 { out.write_u8(0x44).unwrap(); }
+            if version >= 5 { out.write_u32::<LittleEndian>(0x0505_0505).unwrap(); }
+            if version >= 6 { out.write_u8(0x66).unwrap(); }
+
+            out.write_u32::<LittleEndian>(action as u32).unwrap();
+            if action == 1 {
+                for value in [7u32, 2, 20, 1] {
+                    out.write_u32::<LittleEndian>(value).unwrap();
+                }
+                out.extend((0..SIGNAL_DATA_SIZE).map(|i| i as u8));
+                for float_count in [2u32, 3, 4] {
+                    out.write_u32::<LittleEndian>(2).unwrap();
+                    out.write_u32::<LittleEndian>(10 + float_count).unwrap();
+                    out.write_u32::<LittleEndian>(20 + float_count).unwrap();
+                    for i in 0..2 * float_count {
+                        out.write_f32::<LittleEndian>(i as f32 * 0.25 - 1.0).unwrap();
+                    }
+                }
+                out.write_u32::<LittleEndian>(1).unwrap();
+                out.write_u32::<LittleEndian>(5).unwrap();
+                put_string(&mut out, b"fx_hit\0\0");
+                if version >= 6 { out.write_u8(0x99).unwrap(); }
+            }
+        }
+        out
+    }
+
+    fn round_trip(bytes: &[u8]) -> (ActFile, Vec<u8>) {
+        let act = parse_act(&mut &bytes[..]).unwrap();
+        let mut written = Vec::new();
+        write_act_to(&act, &mut written).unwrap();
+        (act, written)
+    }
+
+    #[test]
+    fn round_trip_is_byte_identical() {
+        for version in 2..=LATEST_VERSION {
+            let bytes = fixture(version);
+            let (act, written) = round_trip(&bytes);
+            assert_eq!(act.version, version);
+            assert_eq!(written, bytes, "version {}", version);
+        }
+    }
+
+    #[test]
+    fn version_gated_fields_are_read() {
+        for version in 2..=LATEST_VERSION {
+            let (act, _) = round_trip(&fixture(version));
+            let action = &act.actions[1];
+            let expected = |since: u32, value: u32| if version >= since { value } else { 0 };
+            assert_eq!(action.unk_v2, expected(2, 0x0202_0202));
+            assert_eq!(action.unk_v3, expected(3, 0x0303_0303));
+            assert_eq!(action.unk_v4 as u32, expected(4, 0x44));
+            assert_eq!(action.unk_v5, expected(5, 0x0505_0505));
+            assert_eq!(action.unk_v6 as u32, expected(6, 0x66));
+            assert_eq!(action.signals[0].act6_signal as u32, expected(6, 0x99));
+        }
+    }
+
+    #[test]
+    fn signal_tables_are_read() {
+        let (act, _) = round_trip(&fixture(LATEST_VERSION));
+        assert!(act.actions[0].signals.is_empty());
+        let signal = &act.actions[1].signals[0];
+        assert_eq!((signal.signal_index, signal.start_frame, signal.end_frame, signal.y_order), (7, 2, 20, 1));
+        assert_eq!(signal.data[255], 255);
+        assert_eq!(signal.vec2_table.iter().map(|e| e.index).collect::<Vec<_>>(), vec![12, 22]);
+        assert_eq!(signal.vec3_table[1].values, vec![-0.25, 0.0, 0.25]);
+        assert_eq!(signal.vec4_table[0].values.len(), 4);
+        assert_eq!(signal.string_table[0].index, 5);
+        assert_eq!(signal.string_table[0].data, b"fx_hit\0\0");
+    }
+
+    #[test]
+    fn non_utf8_names_keep_their_bytes() {
+        let (act, _) = round_trip(&fixture(5));
+        assert_eq!(act.actions[1].name.0, b"\xb0\xf8\xb0\xdd");
+        assert_eq!(act.actions[1].next_action_name.0, b"Stand");
+    }
+
+    #[test]
+    fn lower_version_leaves_out_newer_fields() {
+        let mut act = parse_act(&mut &fixture(6)[..]).unwrap();
+        for version in (2..LATEST_VERSION).rev() {
+            act.version = version;
+            let mut written = Vec::new();
+            write_act_to(&act, &mut written).unwrap();
+            let (reread, rewritten) = round_trip(&written);
+            assert_eq!(reread.version, version);
+            assert_eq!(rewritten, written);
+        }
+    }
+
+    #[test]
+    fn truncated_files_are_errors() {
+        let bytes = fixture(LATEST_VERSION);
+        for len in [0, HEADER_SIZE + 2, bytes.len() / 2, bytes.len() - 1] {
+            assert!(parse_act(&mut &bytes[..len]).is_err(), "length {}", len);
+        }
+    }
+
+    #[test]
+    fn unknown_versions_are_errors() {
+        for version in [0, LATEST_VERSION + 1] {
+            let err = parse_act(&mut &fixture(version)[..]).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().contains(&version.to_string()));
+        }
+    }
+
+    #[test]
+    fn huge_counts_fail_on_the_read() {
+        let mut bytes = fixture(LATEST_VERSION);
+        // The first action name's length.
+        let offset = HEADER_SIZE + 8;
+        bytes[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let error = parse_act(&mut &bytes[..]).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn malformed_models_are_not_written() {
+        let mut act = parse_act(&mut &fixture(LATEST_VERSION)[..]).unwrap();
+        act.actions[1].signals[0].vec3_table[0].values.pop();
+        assert!(write_act_to(&act, &mut Vec::new()).is_err());
+
+        let mut act = parse_act(&mut &fixture(LATEST_VERSION)[..]).unwrap();
+        act.actions[1].signals[0].data.truncate(10);
+        assert!(write_act_to(&act, &mut Vec::new()).is_err());
+    }
+}
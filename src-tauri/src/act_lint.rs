@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use byteorder::{LittleEndian, ReadBytesExt};
+use crate::act_file::{self, ActFile, ActString};
+
+const ANI_NAME_SIZE: usize = 256;
+const ANI_HEADER_SIZE: usize = 1024;
+
+pub struct LintIssue {
+    pub action: String,
+    pub message: String,
+}
+
+/// Animation names of a .ani file. The header is a 256-byte name, then version, bone count and
+/// animation count as i32s, padded to 1024 bytes; the animation names follow as 256-byte strings.
+pub fn read_ani_names(path: &Path) -> io::Result<HashSet<ActString>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut header = [0u8; ANI_HEADER_SIZE];
+    reader.read_exact(&mut header)?;
+    let mut counts = &header[ANI_NAME_SIZE..ANI_NAME_SIZE + 12];
+    let _version = counts.read_i32::<LittleEndian>()?;
+    let _bone_count = counts.read_i32::<LittleEndian>()?;
+    let ani_count = counts.read_i32::<LittleEndian>()?;
+    let ani_count = usize::try_from(ani_count)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("{}: negative animation count", path.display())))?;
+
+    // The count comes from the file, so it only bounds the loop, not an allocation.
+    let mut names = HashSet::new();
+    let mut name = [0u8; ANI_NAME_SIZE];
+    for _ in 0..ani_count {
+        reader.read_exact(&mut name)?;
+        let end = name.iter().position(|b| *b == 0).unwrap_or(ANI_NAME_SIZE);
+        names.insert(ActString(name[..end].to_vec()));
+    }
+    Ok(names)
+}
+
+/// Checks the action graph of one file; `ani_names` are the animations of the paired .ani, if found.
+pub fn lint_act(act: &ActFile, ani_names: Option<&HashSet<ActString>>) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |action: &ActString, message: String| issues.push(LintIssue { action: action.to_string(), message });
+
+    let action_names: HashSet<&ActString> = act.actions.iter().map(|a| &a.name).collect();
+    let mut seen = HashSet::new();
+
+    for action in &act.actions {
+        if !seen.insert(&action.name) {
+            issue(&action.name, "duplicate action name".to_string());
+        }
+        if !action.next_action_name.is_empty() && !action_names.contains(&action.next_action_name) {
+            issue(&action.name, format!("next action {} does not exist", action.next_action_name));
+        }
+
+        let blend_frame = action.blend_frame as i32;
+        if blend_frame < 0 {
+            issue(&action.name, format!("negative blend frame {}", blend_frame));
+        } else if action.blend_frame > action.dw_length {
+            issue(&action.name, format!("blend frame {} is longer than the action ({} frames)", action.blend_frame, action.dw_length));
+        }
+
+        for (index, signal) in action.signals.iter().enumerate() {
+            if signal.start_frame > action.dw_length {
+                issue(&action.name, format!(
+                    "signal {} (index {}) starts at frame {}, after the action ends ({} frames)",
+                    index, signal.signal_index, signal.start_frame, action.dw_length
+                ));
+            }
+        }
+
+        if let Some(ani_names) = ani_names {
+            if !action.link_ani_name.is_empty() && !ani_names.contains(&action.link_ani_name) {
+                issue(&action.name, format!("animation {} is not in the .ani file", action.link_ani_name));
+            }
+        }
+    }
+    issues
+}
+
+/// Lints every .act and writes one report; returns the number of issues found. A file that cannot
+/// be read counts as one issue and the rest are still linted.
+pub fn lint_acts(input_files: &[PathBuf], report_file: &str) -> io::Result<usize> {
+    let mut output = BufWriter::new(File::create(report_file)?);
+    let mut total_issues = 0;
+
+    for path in input_files {
+        let act = match act_file::read_act(path.to_str().unwrap()) {
+            Ok(act) => act,
+            Err(e) => {
+                writeln!(output, "{}: {}", path.display(), e)?;
+                total_issues += 1;
+                continue;
+            }
+        };
+
+        let ani_path = path.with_extension("ani");
+        let ani_names = if ani_path.exists() {
+            match read_ani_names(&ani_path) {
+                Ok(names) => Some(names),
+                Err(e) => {
+                    writeln!(output, "{}: {}, animation names not checked", ani_path.display(), e)?;
+                    total_issues += 1;
+                    None
+                }
+            }
+        } else {
+            writeln!(output, "{}: {} not found, animation names not checked", path.display(), ani_path.display())?;
+            None
+        };
+
+        let issues = lint_act(&act, ani_names.as_ref());
+        for issue in &issues {
+            writeln!(output, "{}: action {}: {}", path.display(), issue.action, issue.message)?;
+        }
+        total_issues += issues.len();
+    }
+
+    output.flush()?;
+    Ok(total_issues)
+}
@@ -0,0 +1,171 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::act_file::{ActFile, Signal, SIGNAL_DATA_SIZE};
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Int,
+    Uint,
+    Float,
+    Byte,
+    /// i32 holding 0 or 1.
+    Bool,
+    /// Zero-padded text of `length` bytes.
+    String,
+}
+
+#[derive(Deserialize)]
+pub struct FieldDefinition {
+    pub name: String,
+    pub offset: usize,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    #[serde(default)]
+    pub length: usize,
+}
+
+#[derive(Deserialize)]
+pub struct SignalDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub fields: Vec<FieldDefinition>,
+}
+
+/// Layouts of the 256-byte signal data block, keyed by signal index.
+#[derive(Deserialize)]
+#[serde(transparent)]
+pub struct SignalDefinitions {
+    pub signals: IndexMap<u32, SignalDefinition>,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl FieldDefinition {
+    fn size(&self) -> usize {
+        match self.field_type {
+            FieldType::Byte => 1,
+            FieldType::String => self.length,
+            _ => 4,
+        }
+    }
+}
+
+/// Loads a signal definition file like `{ "0": { "name": "Hit", "fields": [{ "name": "Damage", "offset": 0, "type": "int" }] } }`.
+pub fn load_signal_definitions(path: &str) -> io::Result<SignalDefinitions> {
+    let definitions: SignalDefinitions = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+    for (index, signal) in &definitions.signals {
+        for field in &signal.fields {
+            if matches!(field.field_type, FieldType::String) && field.length == 0 {
+                return Err(invalid(format!("{}: signal {} field {} needs a length", path, index, field.name)));
+            }
+            if field.offset + field.size() > SIGNAL_DATA_SIZE {
+                return Err(invalid(format!(
+                    "{}: signal {} field {} ends past the {}-byte data block", path, index, field.name, SIGNAL_DATA_SIZE
+                )));
+            }
+        }
+    }
+    Ok(definitions)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn decode_field(data: &[u8], field: &FieldDefinition) -> Value {
+    let offset = field.offset;
+    match field.field_type {
+        FieldType::Int => Value::from(read_u32(data, offset) as i32),
+        FieldType::Uint => Value::from(read_u32(data, offset)),
+        // Going through the f32's shortest text keeps 0.1 from turning into 0.10000000149011612.
+        FieldType::Float => {
+            let value = f32::from_bits(read_u32(data, offset));
+            value.to_string().parse::<f64>().ok()
+                .and_then(serde_json::Number::from_f64)
+                .map_or(Value::Null, Value::Number)
+        }
+        FieldType::Byte => Value::from(data[offset]),
+        FieldType::Bool => Value::from(read_u32(data, offset) != 0),
+        FieldType::String => {
+            let bytes = &data[offset..offset + field.length];
+            let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+            Value::from(String::from_utf8_lossy(&bytes[..end]).to_string())
+        }
+    }
+}
+
+fn encode_field(data: &mut [u8], field: &FieldDefinition, value: &Value) -> Option<()> {
+    let offset = field.offset;
+    let bytes = match field.field_type {
+        FieldType::Int => (i32::try_from(value.as_i64()?).ok()?).to_le_bytes().to_vec(),
+        FieldType::Uint => (u32::try_from(value.as_u64()?).ok()?).to_le_bytes().to_vec(),
+        FieldType::Float => (value.as_f64()? as f32).to_le_bytes().to_vec(),
+        FieldType::Byte => vec![u8::try_from(value.as_u64()?).ok()?],
+        FieldType::Bool => (value.as_bool()? as u32).to_le_bytes().to_vec(),
+        FieldType::String => {
+            let text = value.as_str()?.as_bytes();
+            if text.len() > field.length {
+                return None;
+            }
+            let mut bytes = vec![0; field.length];
+            bytes[..text.len()].copy_from_slice(text);
+            bytes
+        }
+    };
+    data[offset..offset + bytes.len()].copy_from_slice(&bytes);
+    Some(())
+}
+
+/// Fills in the signal name and decoded fields of every signal that has a definition.
+pub fn decode_signals(act: &mut ActFile, definitions: &SignalDefinitions) {
+    for signal in act.actions.iter_mut().flat_map(|action| action.signals.iter_mut()) {
+        if let Some(definition) = definitions.signals.get(&signal.signal_index) {
+            signal.signal_name = Some(definition.name.clone());
+            signal.fields = definition.fields.iter()
+                .map(|field| (field.name.clone(), decode_field(&signal.data, field)))
+                .collect();
+        }
+    }
+}
+
+fn encode_signal(signal: &mut Signal, definitions: &SignalDefinitions) -> Result<(), String> {
+    if signal.fields.is_empty() {
+        return Ok(());
+    }
+    if signal.data.len() != SIGNAL_DATA_SIZE {
+        return Err(format!("signal data is {} bytes, expected {}", signal.data.len(), SIGNAL_DATA_SIZE));
+    }
+    let definition = definitions.signals.get(&signal.signal_index)
+        .ok_or_else(|| format!("no definition for signal index {}", signal.signal_index))?;
+
+    let original = signal.data.clone();
+    for (name, value) in &signal.fields {
+        let field = definition.fields.iter().find(|field| field.name == *name)
+            .ok_or_else(|| format!("{} has no field {}", definition.name, name))?;
+        if decode_field(&original, field) == *value {
+            continue;
+        }
+        encode_field(&mut signal.data, field, value)
+            .ok_or_else(|| format!("{} is not valid for field {}", value, name))?;
+    }
+    Ok(())
+}
+
+/// Writes edited fields back into the data block. Fields that still decode to the exported value and
+/// bytes no field covers are left as they are, so values the text form cannot hold exactly (NaN, a
+/// bool of 2, bytes after a string's NUL) survive an unedited round trip.
+pub fn encode_signals(act: &mut ActFile, definitions: &SignalDefinitions) -> io::Result<()> {
+    for action in act.actions.iter_mut() {
+        for (index, signal) in action.signals.iter_mut().enumerate() {
+            encode_signal(signal, definitions)
+                .map_err(|e| invalid(format!("Action {} signal {}: {}", action.name, index, e)))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use encoding_rs::Encoding;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::dnt_converter::{self, DntColumn, DntRow, DntType};
+use crate::dnt_patch;
+use crate::dnt_schema::SchemaRegistry;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TypeSource {
+    Code(u8),
+    Name(String),
+}
+
+#[derive(Deserialize)]
+struct ColumnSource {
+    name: String,
+    #[serde(rename = "type")]
+    type_source: TypeSource,
+    #[serde(default)]
+    default: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct TemplateSource {
+    #[serde(default)]
+    extends: Option<String>,
+    #[serde(flatten)]
+    values: IndexMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct RowSource {
+    id: u32,
+    #[serde(default)]
+    template: Option<String>,
+    #[serde(flatten)]
+    values: IndexMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct TableSource {
+    columns: Vec<ColumnSource>,
+    #[serde(default)]
+    templates: IndexMap<String, TemplateSource>,
+    #[serde(default)]
+    rows: Vec<RowSource>,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn type_code(column: &ColumnSource) -> io::Result<u8> {
+    let (code, text) = match &column.type_source {
+        TypeSource::Code(code) => (DntType::from_code(*code).map(|_| *code), code.to_string()),
+        TypeSource::Name(name) => (DntType::code_from_name(name), name.clone()),
+    };
+    code.ok_or_else(|| invalid(format!("Unknown type {} for column {}", text, column.name)))
+}
+
+fn load_source(input_file: &str) -> io::Result<TableSource> {
+    let text = fs::read_to_string(input_file)?;
+    let lower = input_file.to_lowercase();
+    if lower.ends_with(".toml") {
+        toml::from_str(&text).map_err(|e| invalid(format!("{}: {}", input_file, e)))
+    } else if lower.ends_with(".yaml") || lower.ends_with(".yml") {
+        serde_yaml::from_str(&text).map_err(|e| invalid(format!("{}: {}", input_file, e)))
+    } else {
+        Err(invalid(format!("{}: expected a .yaml, .yml or .toml file", input_file)))
+    }
+}
+
+/// Values of a template with everything it extends applied first.
+fn template_values<'a>(source: &'a TableSource, name: &str, chain: &mut Vec<String>) -> io::Result<IndexMap<&'a str, &'a Value>> {
+    if chain.iter().any(|n| n == name) {
+        chain.push(name.to_string());
+        return Err(invalid(format!("Template cycle: {}", chain.join(" -> "))));
+    }
+    let template = source.templates.get(name)
+        .ok_or_else(|| invalid(format!("Unknown template {}", name)))?;
+    chain.push(name.to_string());
+
+    let mut values = match &template.extends {
+        Some(parent) => template_values(source, parent, chain)?,
+        None => IndexMap::new(),
+    };
+    values.extend(template.values.iter().map(|(k, v)| (k.as_str(), v)));
+    Ok(values)
+}
+
+/// Compiles a YAML or TOML table description into a .dnt; returns the number of rows written.
+pub fn compile_table(input_file: &str, output_file: &str, encoding: &'static Encoding, schemas: &SchemaRegistry) -> io::Result<usize> {
+    let source = load_source(input_file)?;
+
+    let mut columns = Vec::with_capacity(source.columns.len());
+    let mut defaults = Vec::with_capacity(source.columns.len());
+    for column in &source.columns {
+        let type_byte = type_code(column)?;
+        let default = match &column.default {
+            Some(value) => dnt_patch::json_to_value(value, type_byte)
+                .ok_or_else(|| invalid(format!("Default {} is not valid for column {}", value, column.name)))?,
+            None => dnt_converter::default_value(type_byte),
+        };
+        columns.push(DntColumn { name: column.name.clone(), type_byte });
+        defaults.push(default);
+    }
+
+    let mut seen_ids = HashSet::new();
+    let mut rows = Vec::with_capacity(source.rows.len());
+    for row in &source.rows {
+        if !seen_ids.insert(row.id) {
+            return Err(invalid(format!("Duplicate row id {}", row.id)));
+        }
+
+        let mut values = match &row.template {
+            Some(template) => template_values(&source, template, &mut Vec::new())
+                .map_err(|e| invalid(format!("Row {}: {}", row.id, e)))?,
+            None => IndexMap::new(),
+        };
+        values.extend(row.values.iter().map(|(k, v)| (k.as_str(), v)));
+
+        if let Some(unknown) = values.keys().find(|name| !columns.iter().any(|c| c.name == **name)) {
+            return Err(invalid(format!("Row {}: unknown column {}", row.id, unknown)));
+        }
+
+        let mut row_values = Vec::with_capacity(columns.len());
+        for (column, default) in columns.iter().zip(&defaults) {
+            let value = match values.get(column.name.as_str()) {
+                Some(value) => dnt_patch::json_to_value(value, column.type_byte).ok_or_else(|| invalid(format!(
+                    "Row {}: {} is not valid for column {}", row.id, value, column.name
+                )))?,
+                None => default.clone(),
+            };
+            row_values.push(value);
+        }
+        rows.push(DntRow { id: row.id, values: row_values });
+    }
+
+    let total_rows = rows.len();
+    dnt_converter::write_dnt(&dnt_converter::DntTable { columns, rows }, output_file, encoding, schemas)?;
+    Ok(total_rows)
+}
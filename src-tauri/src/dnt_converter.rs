@@ -1,70 +1,220 @@
-use indexmap::IndexMap;
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{self, BufReader, BufWriter, Read, Write, Seek, BufRead};
+use std::path::Path;
+use std::io::{self, BufReader, BufWriter, Read, Write, Seek, SeekFrom, BufRead};
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::str;
+use std::fmt;
+use serde::Serialize;
+use encoding_rs::{Encoding, BIG5, EUC_KR, GBK, UTF_8};
+use crate::dnt_schema::{self, SchemaRegistry};
+use crate::uistring::{self, UiStrings};
+use crate::localization::Catalog;
 
-pub fn convert_to_tsv(input_file: &str, output_file: &str) -> io::Result<()> {
-    let mut fs = BufReader::new(File::open(input_file)?);
+/// Writes the table as TSV and returns the `_RowID` problems found on the way.
+pub fn convert_to_tsv(input_file: &str, output_file: &str, encoding: &'static Encoding, uistrings: Option<&UiStrings>) -> io::Result<RowIdFindings> {
+    let mut reader = DntReader::open(input_file, encoding)?.check_row_ids();
+    let mut output = BufWriter::new(File::create(output_file)?);
 
-    fs.seek(std::io::SeekFrom::Current(4))?;
-    let column_count = fs.read_u16::<LittleEndian>()?;
-    let row_count = fs.read_u32::<LittleEndian>()?;
+    let annotated: Vec<bool> = reader.columns().iter()
+        .map(|column| uistrings.is_some() && matches!(column.type_byte, 2 | 3) && uistring::is_string_id_column(&column.name))
+        .collect();
 
-    let mut type_dictionary = IndexMap::new();
+    let mut headers = vec!["_RowID|3".to_string()];
+    for (column, annotated) in reader.columns().iter().zip(&annotated) {
+        headers.push(format!("{}|{}", column.name, column.type_byte));
+        if *annotated {
+            headers.push(format!("{}|1", uistring::annotation_column(&column.name)));
+        }
+    }
+    writeln!(output, "{}", headers.join("\t"))?;
 
-    type_dictionary.insert("_RowID".to_string(), 3);
+    for row in reader.by_ref() {
+        let row = row?;
+        let mut row_data = Vec::with_capacity(headers.len());
+        row_data.push(row.id.to_string());
 
-    for _ in 0..column_count {
-        let length = fs.read_u16::<LittleEndian>()?;
-        let mut name_bytes = vec![0; length as usize];
-        fs.read_exact(&mut name_bytes)?;
-        let name = str::from_utf8(&name_bytes).unwrap();
-        let type_byte = fs.read_u8()?;
-        type_dictionary.insert(name.to_string(), type_byte);
+        for (value, annotated) in row.values.iter().zip(&annotated) {
+            row_data.push(match value {
+                DntValue::Text(v) => v.replace(",", "^"),
+                other => other.to_string(),
+            });
+            if let (true, Some(uistrings), DntValue::Int(id)) = (*annotated, uistrings, value) {
+                row_data.push(uistrings.display_text(*id));
+            }
+        }
+        writeln!(output, "{}", row_data.join("\t"))?;
     }
+    output.flush()?;
 
-    let mut output = BufWriter::new(File::create(output_file)?);
+    Ok(reader.row_id_findings().clone())
+}
 
-    let headers: Vec<String> = type_dictionary.iter()
-    .map(|(name, type_byte)| format!("{}|{}", name, type_byte))
-    .collect();
-    writeln!(output, "{}", headers.join("\t"))?;
+/// String encodings a table can be read and written in. UTF-16 is not among them: encoding_rs
+/// decodes it but encodes back to UTF-8, so a round trip would change the file.
+const SUPPORTED_ENCODINGS: [&Encoding; 4] = [UTF_8, EUC_KR, GBK, BIG5];
+
+pub fn encoding_from_name(name: &str) -> io::Result<&'static Encoding> {
+    Encoding::for_label(name.trim().as_bytes())
+        .filter(|encoding| SUPPORTED_ENCODINGS.contains(encoding))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Unsupported encoding \"{}\"", name)))
+}
+
+fn decode_text(bytes: &[u8], encoding: &'static Encoding) -> String {
+    encoding.decode_without_bom_handling(bytes).0.into_owned()
+}
+
+fn encode_text(text: &str, encoding: &'static Encoding) -> io::Result<Vec<u8>> {
+    let (bytes, _, had_errors) = encoding.encode(text);
+    if had_errors {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("\"{}\" cannot be represented in {}", text, encoding.name()),
+        ));
+    }
+    Ok(bytes.into_owned())
+}
+
+fn tsv_text(value: &str) -> String {
+    if value.is_empty() || value == "0.0" {
+        String::new()
+    } else if let Some(stripped) = value.strip_suffix(".0") {
+        stripped.replace("^", ",")
+    } else {
+        value.replace("^", ",")
+    }
+}
 
-    for _ in 0..row_count {
-        let num4 = fs.read_u32::<LittleEndian>()?;
-        let mut row_data = Vec::with_capacity(type_dictionary.len());
-        row_data.push(num4.to_string());
+pub struct TsvImportOptions {
+    pub strict: bool,
+    pub encoding: &'static Encoding,
+    /// Translated strings to put back into the table, keyed by table/row/column.
+    pub translations: Option<Catalog>,
+    pub sort_by_row_id: bool,
+}
+
+impl Default for TsvImportOptions {
+    fn default() -> Self {
+        TsvImportOptions { strict: false, encoding: UTF_8, translations: None, sort_by_row_id: false }
+    }
+}
+
+const MAX_REPORTED_ERRORS: usize = 100;
+const MAX_PREALLOCATED_ROWS: usize = 65536;
+
+fn read_tsv_strict(input_file: &str) -> io::Result<DntTable> {
+    let reader = BufReader::new(File::open(input_file)?);
+    let mut lines = reader.lines();
+    let mut errors = Vec::new();
+
+    let header = lines.next().transpose()?.unwrap_or_default();
+    let header = header.trim_end_matches(&['\r', '\n'][..]);
+    let mut header_types = Vec::new();
+    let mut columns = Vec::new();
+    let mut annotations = Vec::new();
 
-        for (col_name, type_byte) in &type_dictionary {
-            if col_name.contains("_RowID") {
+    for (index, field) in header.split('\t').enumerate() {
+        let parts: Vec<&str> = field.split('|').collect();
+        let type_byte = match parts.as_slice() {
+            [name, type_text] if !name.is_empty() => match type_text.parse::<u8>() {
+                Ok(type_byte) if DntType::from_code(type_byte).is_some() => Some((name.to_string(), type_byte)),
+                _ => {
+                    errors.push(format!("{}:1:{}: unknown type \"{}\" for column {}", input_file, index + 1, type_text, name));
+                    None
+                }
+            },
+            _ => {
+                errors.push(format!("{}:1:{}: header \"{}\" is not in name|type form", input_file, index + 1, field));
+                None
+            }
+        };
+
+        let (name, type_byte) = type_byte.unwrap_or_else(|| (field.to_string(), 0));
+        if index == 0 {
+            if name != "_RowID" || !matches!(type_byte, 2 | 3) {
+                errors.push(format!("{}:1:1: first column must be _RowID|3, found \"{}\"", input_file, field));
+            }
+        } else if uistring::is_annotation_column(&name) {
+            annotations.push(index);
+        } else {
+            columns.push(DntColumn { name, type_byte });
+        }
+        header_types.push(type_byte);
+    }
+
+    let mut rows = Vec::new();
+    for (line_index, line) in lines.enumerate() {
+        let line_number = line_index + 2;
+        let line = line?;
+        let line = line.trim_end_matches(&['\r', '\n'][..]);
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let cells: Vec<&str> = line.split('\t').collect();
+        if cells.len() != header_types.len() {
+            errors.push(format!(
+                "{}:{}: expected {} columns, found {}", input_file, line_number, header_types.len(), cells.len()
+            ));
+            continue;
+        }
+
+        let id = match cells[0].trim().parse::<u32>() {
+            Ok(id) => id,
+            Err(_) => {
+                errors.push(format!("{}:{}:1: invalid _RowID \"{}\"", input_file, line_number, cells[0]));
                 continue;
             }
-            let value = match type_byte {
-                1 => {
-                    let length = fs.read_i16::<LittleEndian>()?;
-                    if length > 0 {
-                        let mut string_value = vec![0; length as usize];
-                        fs.read_exact(&mut string_value)?;
-                        String::from_utf8_lossy(&string_value).replace(",", "^").to_string()
-                    } else {
-                        String::new()
-                    }
-                },
-                2 | 3 => fs.read_i32::<LittleEndian>()?.to_string(),
-                4 | 5 => fs.read_f32::<LittleEndian>()?.to_string(),
-                6 => fs.read_f64::<LittleEndian>()?.to_string(),
-                _ => String::new(),
+        };
+
+        let mut values = Vec::with_capacity(columns.len());
+        let data_cells = cells.iter().enumerate().skip(1).filter(|(index, _)| !annotations.contains(index));
+        for ((index, cell), column) in data_cells.zip(&columns) {
+            let value = if column.type_byte == 1 {
+                Some(DntValue::Text(tsv_text(cell)))
+            } else {
+                parse_value(column.type_byte, cell)
             };
-            row_data.push(value);
+            match value {
+                Some(value) => values.push(value),
+                None if column.type_byte == 0 => {}
+                None => errors.push(format!(
+                    "{}:{}:{}: \"{}\" is not a valid value for {}|{}",
+                    input_file, line_number, index + 1, cell, column.name, column.type_byte
+                )),
+            }
         }
-        writeln!(output, "{}", row_data.join("\t"))?;
+        rows.push(DntRow { id, values });
     }
 
-    Ok(())
+    if !errors.is_empty() {
+        let total = errors.len();
+        errors.truncate(MAX_REPORTED_ERRORS);
+        if total > MAX_REPORTED_ERRORS {
+            errors.push(format!("... and {} more errors", total - MAX_REPORTED_ERRORS));
+        }
+        return Err(io::Error::new(io::ErrorKind::InvalidData, errors.join("\n")));
+    }
+
+    Ok(DntTable { columns, rows })
 }
 
-pub fn convert_to_dnt(input_file: &str, output_file: &str) -> io::Result<()> {
+pub fn convert_to_dnt(input_file: &str, output_file: &str, options: &TsvImportOptions, schemas: &SchemaRegistry) -> io::Result<()> {
+    let table_name = dnt_schema::table_name(Path::new(output_file));
+
+    if options.strict {
+        let mut table = read_tsv_strict(input_file)?;
+        if let Some(catalog) = &options.translations {
+            let names: Vec<&str> = table.columns.iter().map(|c| c.name.as_str()).collect();
+            for row in table.rows.iter_mut() {
+                catalog.translate_row(&table_name, &names, row);
+            }
+        }
+        if options.sort_by_row_id {
+            sort_rows(input_file, &mut table.rows)?;
+        }
+        return write_dnt(&table, output_file, options.encoding, schemas);
+    }
+
     let file = File::open(input_file)?;
     let mut reader = BufReader::new(file);
 
@@ -72,86 +222,486 @@ pub fn convert_to_dnt(input_file: &str, output_file: &str) -> io::Result<()> {
     reader.read_line(&mut first_line)?;
 
     let fields: Vec<&str> = first_line.trim().split('\t').collect();
-    let field_count = (fields.len() - 1) as u16;
-    let row_lines: Vec<String> = reader.lines()
-    .filter_map(|line| {
-        match line {
-            Ok(l) if !l.trim().is_empty() => Some(l),
+    let mut columns = Vec::with_capacity(fields.len());
+    let mut column_cells = Vec::with_capacity(fields.len());
+    for (index, field) in fields.iter().enumerate() {
+        let parts: Vec<&str> = field.split('|').collect();
+        if parts.len() == 2 {
+            let field_name = parts[0];
+            let field_type = parts[1].parse::<u8>().unwrap_or(0);
+            if index == 0 || uistring::is_annotation_column(field_name) {
+                continue
+            };
+            columns.push(DntColumn { name: field_name.to_string(), type_byte: field_type });
+            column_cells.push(index);
+        }
+    }
+
+    let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+    let rows = reader.lines().filter_map(|line| {
+        let line = match line {
+            Ok(line) if line.trim().is_empty() => return None,
+            Ok(line) => line,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let cells: Vec<&str> = line.split('\t').collect();
+        let id = cells[0].trim().parse::<i32>().unwrap_or(0) as u32;
+        let values = columns.iter().zip(&column_cells)
+            .map(|(column, index)| lenient_value(column.type_byte, cells.get(*index).copied().unwrap_or("")))
+            .collect();
+        let mut row = DntRow { id, values };
+        if let Some(catalog) = &options.translations {
+            catalog.translate_row(&table_name, &names, &mut row);
+        }
+        Some(Ok(row))
+    });
+
+    if options.sort_by_row_id {
+        let mut rows = rows.collect::<io::Result<Vec<DntRow>>>()?;
+        sort_rows(input_file, &mut rows)?;
+        write_rows(output_file, columns.clone(), rows.into_iter().map(Ok), options.encoding, schemas)
+    } else {
+        write_rows(output_file, columns.clone(), rows, options.encoding, schemas)
+    }
+}
+
+/// Sorts rows by `_RowID`, refusing tables where an ID appears more than once.
+fn sort_rows(input_file: &str, rows: &mut [DntRow]) -> io::Result<()> {
+    rows.sort_by_key(|row| row.id);
+
+    let mut duplicates: Vec<String> = rows.windows(2)
+        .filter(|pair| pair[0].id == pair[1].id)
+        .map(|pair| pair[0].id.to_string())
+        .collect();
+    duplicates.dedup();
+
+    if duplicates.is_empty() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: duplicate _RowID {}", input_file, duplicates.join(", ")),
+        ))
+    }
+}
+
+fn write_rows<I: IntoIterator<Item = io::Result<DntRow>>>(output_file: &str, columns: Vec<DntColumn>, rows: I, encoding: &'static Encoding, schemas: &SchemaRegistry) -> io::Result<()> {
+    let mut writer = DntWriter::create(output_file, columns, encoding, schemas)?;
+    for row in rows {
+        writer.write_row(&row?)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+fn lenient_value(type_byte: u8, text: &str) -> DntValue {
+    match DntType::from_code(type_byte) {
+        Some(DntType::String) => DntValue::Text(tsv_text(text)),
+        Some(DntType::Bool) => parse_value(type_byte, text).unwrap_or(DntValue::Int(0)),
+        Some(DntType::Int) => DntValue::Int(text.trim().parse().unwrap_or(0)),
+        Some(DntType::Float | DntType::PercentFloat) => DntValue::Float(text.trim().parse().unwrap_or(0.0)),
+        Some(DntType::Double) => DntValue::Double(text.trim().parse().unwrap_or(0.0)),
+        // The writer rejects the column, so the value itself does not matter.
+        None => default_value(type_byte),
+    }
+}
+
+/// Column type codes of the .dnt format. These are the only codes found in client tables;
+/// anything else is refused rather than guessed at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DntType {
+    /// 1: u16 byte length followed by the encoded text.
+    String,
+    /// 2: i32 holding 0 or 1.
+    Bool,
+    /// 3: i32.
+    Int,
+    /// 4: f32.
+    Float,
+    /// 5: f32 used for rates and percentages.
+    PercentFloat,
+    /// 6: f64.
+    Double,
+}
+
+impl DntType {
+    pub fn from_code(code: u8) -> Option<DntType> {
+        match code {
+            1 => Some(DntType::String),
+            2 => Some(DntType::Bool),
+            3 => Some(DntType::Int),
+            4 => Some(DntType::Float),
+            5 => Some(DntType::PercentFloat),
+            6 => Some(DntType::Double),
             _ => None,
         }
-    })
-    .collect();
+    }
 
-    let row_count = row_lines.len() as u32;
+    /// Type code for a readable type name, as used in table sources.
+    pub fn code_from_name(name: &str) -> Option<u8> {
+        match name.to_lowercase().as_str() {
+            "string" | "text" => Some(1),
+            "bool" => Some(2),
+            "int" => Some(3),
+            "float" => Some(4),
+            "percent" => Some(5),
+            "double" => Some(6),
+            _ => None,
+        }
+    }
+}
 
-    let mut fs = BufWriter::new(File::create(output_file)?);
+#[derive(Clone)]
+pub struct DntColumn {
+    pub name: String,
+    pub type_byte: u8,
+}
 
-    fs.write_all(&[0; 4])?;
-    fs.write_u16::<LittleEndian>(field_count)?;
-    fs.write_u32::<LittleEndian>(row_count as u32)?;
+impl DntColumn {
+    pub fn data_type(&self) -> io::Result<DntType> {
+        DntType::from_code(self.type_byte).ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unknown type code {} in column {}", self.type_byte, self.name),
+        ))
+    }
+}
 
-    let mut field_types = Vec::with_capacity(fields.len());
-    for field in fields.iter() {
-        let parts: Vec<&str> = field.split('|').collect();
-        if parts.len() == 2 {
-            let field_name = parts[0];
-            let field_type = parts[1].parse::<u8>().unwrap_or(0);
-            if field_name.contains("RowID") { 
-                field_types.push(field_type);
-                continue 
-            }; 
-            field_types.push(field_type);
-
-            let field_name_length = field_name.len() as u16;
-            fs.write_u16::<LittleEndian>(field_name_length)?;
-            fs.write_all(field_name.as_bytes())?;
-            fs.write_u8(field_type)?;
-        }
-    }
-
-    for row in row_lines.iter() {
-        let row_fields: Vec<&str> = row.trim().split('\t').collect();
-        for (index, value) in row_fields.iter().enumerate() {
-            let field_type = *field_types.get(index).unwrap_or(&0);
-            match field_type {
-                1 => {
-                    let encoded_value = if value.is_empty() || *value == "0.0" {
-                        Vec::new()
-                    } else if value.ends_with(".0") {
-                        value[..value.len() - 2]
-                            .replace("^", ",")
-                            .as_bytes()
-                            .to_vec()
-                    } else {
-                        value.replace("^", ",")
-                            .as_bytes()
-                            .to_vec()
-                    };
-
-                    fs.write_u16::<LittleEndian>(encoded_value.len() as u16)?;
-                    fs.write_all(&encoded_value)?;
-                }
-                2 | 3 => {
-                    let int_value: i32 = value.parse().unwrap_or(0);
-                    fs.write_i32::<LittleEndian>(int_value)?;
-                }
-                4 | 5 => {
-                    let float_value: f32 = value.parse().unwrap_or(0.0);
-                    fs.write_f32::<LittleEndian>(float_value)?;
-                }
-                6 => {
-                    let double_value: f64 = value.parse().unwrap_or(0.0);
-                    fs.write_f64::<LittleEndian>(double_value)?;
-                }
-                _ => {
-                    fs.write_u8(0)?;
-                }
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum DntValue {
+    Text(String),
+    Int(i32),
+    Float(f32),
+    Double(f64),
+}
+
+impl fmt::Display for DntValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DntValue::Text(v) => write!(f, "{}", v),
+            DntValue::Int(v) => write!(f, "{}", v),
+            DntValue::Float(v) => write!(f, "{}", v),
+            DntValue::Double(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+pub struct DntRow {
+    pub id: u32,
+    pub values: Vec<DntValue>,
+}
+
+pub struct DntTable {
+    pub columns: Vec<DntColumn>,
+    pub rows: Vec<DntRow>,
+}
+
+fn read_header<R: Read>(fs: &mut R, encoding: &'static Encoding) -> io::Result<(Vec<DntColumn>, u32)> {
+    let mut reserved = [0; 4];
+    fs.read_exact(&mut reserved)?;
+    let column_count = fs.read_u16::<LittleEndian>()?;
+    let row_count = fs.read_u32::<LittleEndian>()?;
+
+    let mut columns = Vec::with_capacity(column_count as usize);
+    for _ in 0..column_count {
+        let length = fs.read_u16::<LittleEndian>()?;
+        let mut name_bytes = vec![0; length as usize];
+        fs.read_exact(&mut name_bytes)?;
+        let name = decode_text(&name_bytes, encoding);
+        let type_byte = fs.read_u8()?;
+        let column = DntColumn { name, type_byte };
+        column.data_type()?;
+        columns.push(column);
+    }
+
+    Ok((columns, row_count))
+}
+
+const MAX_LISTED_ROW_IDS: usize = 10;
+
+/// Duplicate and out-of-order `_RowID`s of one table.
+#[derive(Clone, Debug, Default)]
+pub struct RowIdFindings {
+    pub duplicate_row_ids: Vec<u32>,
+    pub out_of_order_rows: usize,
+}
+
+impl RowIdFindings {
+    pub fn is_empty(&self) -> bool {
+        self.duplicate_row_ids.is_empty() && self.out_of_order_rows == 0
+    }
+}
+
+impl fmt::Display for RowIdFindings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if !self.duplicate_row_ids.is_empty() {
+            let mut ids: Vec<String> = self.duplicate_row_ids.iter().take(MAX_LISTED_ROW_IDS).map(|id| id.to_string()).collect();
+            if self.duplicate_row_ids.len() > MAX_LISTED_ROW_IDS {
+                ids.push("...".to_string());
+            }
+            parts.push(format!("{} duplicate _RowIDs ({})", self.duplicate_row_ids.len(), ids.join(", ")));
+        }
+        if self.out_of_order_rows > 0 {
+            parts.push(format!("{} rows out of _RowID order", self.out_of_order_rows));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Reads a .dnt one row at a time so large tables never have to fit in memory.
+/// After `check_row_ids`, duplicate and out-of-order `_RowID`s are collected while reading.
+pub struct DntReader<R: Read> {
+    reader: R,
+    columns: Vec<DntColumn>,
+    row_count: u32,
+    rows_read: u32,
+    encoding: &'static Encoding,
+    last_id: Option<u32>,
+    /// Every ID read so far; only kept when checking, since it grows with the table.
+    seen_ids: Option<HashSet<u32>>,
+    findings: RowIdFindings,
+}
+
+impl DntReader<BufReader<File>> {
+    pub fn open(input_file: &str, encoding: &'static Encoding) -> io::Result<Self> {
+        DntReader::new(BufReader::new(File::open(input_file)?), encoding)
+    }
+}
+
+impl<R: Read> DntReader<R> {
+    pub fn new(mut reader: R, encoding: &'static Encoding) -> io::Result<Self> {
+        let (columns, row_count) = read_header(&mut reader, encoding)?;
+        Ok(DntReader {
+            reader,
+            columns,
+            row_count,
+            rows_read: 0,
+            encoding,
+            last_id: None,
+            seen_ids: None,
+            findings: RowIdFindings::default(),
+        })
+    }
+
+    /// Collects duplicate and out-of-order `_RowID`s while the rows are read.
+    pub fn check_row_ids(mut self) -> Self {
+        self.seen_ids = Some(HashSet::new());
+        self
+    }
+
+    pub fn row_id_findings(&self) -> &RowIdFindings {
+        &self.findings
+    }
+
+    fn check_row_id(&mut self, id: u32) {
+        let Some(seen_ids) = &mut self.seen_ids else {
+            return;
+        };
+        if !seen_ids.insert(id) {
+            if !self.findings.duplicate_row_ids.contains(&id) {
+                self.findings.duplicate_row_ids.push(id);
+            }
+        } else if self.last_id.is_some_and(|last| id < last) {
+            self.findings.out_of_order_rows += 1;
+        }
+        self.last_id = Some(id);
+    }
+
+    pub fn columns(&self) -> &[DntColumn] {
+        &self.columns
+    }
+
+    pub fn row_count(&self) -> u32 {
+        self.row_count
+    }
+
+    pub fn into_columns(self) -> Vec<DntColumn> {
+        self.columns
+    }
+
+    fn read_row(&mut self) -> io::Result<DntRow> {
+        let id = self.reader.read_u32::<LittleEndian>()?;
+        let mut values = Vec::with_capacity(self.columns.len());
+        for column in &self.columns {
+            values.push(read_value(&mut self.reader, column, self.encoding)?);
+        }
+        Ok(DntRow { id, values })
+    }
+}
+
+impl<R: Read> Iterator for DntReader<R> {
+    type Item = io::Result<DntRow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rows_read >= self.row_count {
+            return None;
+        }
+        let row = self.read_row();
+        // A broken row leaves the stream misaligned, so stop after reporting it.
+        match &row {
+            Ok(row) => {
+                self.rows_read += 1;
+                self.check_row_id(row.id);
             }
+            Err(_) => self.rows_read = self.row_count,
+        }
+        Some(row)
+    }
+}
+
+pub fn read_dnt_columns(input_file: &str, encoding: &'static Encoding) -> io::Result<Vec<DntColumn>> {
+    Ok(DntReader::open(input_file, encoding)?.into_columns())
+}
+
+pub fn read_dnt(input_file: &str, encoding: &'static Encoding) -> io::Result<DntTable> {
+    let mut reader = DntReader::open(input_file, encoding)?;
+    // The header count is only a hint; a corrupt file must not turn into a huge allocation.
+    let mut rows = Vec::with_capacity((reader.row_count() as usize).min(MAX_PREALLOCATED_ROWS));
+    for row in reader.by_ref() {
+        rows.push(row?);
+    }
+
+    Ok(DntTable { columns: reader.into_columns(), rows })
+}
+
+fn read_value<R: Read>(reader: &mut R, column: &DntColumn, encoding: &'static Encoding) -> io::Result<DntValue> {
+    match column.data_type()? {
+        DntType::String => {
+            let length = reader.read_i16::<LittleEndian>()?;
+            if length > 0 {
+                let mut string_value = vec![0; length as usize];
+                reader.read_exact(&mut string_value)?;
+                Ok(DntValue::Text(decode_text(&string_value, encoding)))
+            } else {
+                Ok(DntValue::Text(String::new()))
+            }
+        },
+        DntType::Bool | DntType::Int => Ok(DntValue::Int(reader.read_i32::<LittleEndian>()?)),
+        DntType::Float | DntType::PercentFloat => Ok(DntValue::Float(reader.read_f32::<LittleEndian>()?)),
+        DntType::Double => Ok(DntValue::Double(reader.read_f64::<LittleEndian>()?)),
+    }
+}
+
+pub fn default_value(type_byte: u8) -> DntValue {
+    match DntType::from_code(type_byte) {
+        Some(DntType::String) => DntValue::Text(String::new()),
+        Some(DntType::Float | DntType::PercentFloat) => DntValue::Float(0.0),
+        Some(DntType::Double) => DntValue::Double(0.0),
+        _ => DntValue::Int(0),
+    }
+}
+
+pub fn parse_value(type_byte: u8, text: &str) -> Option<DntValue> {
+    let number = text.trim();
+    match DntType::from_code(type_byte)? {
+        DntType::String => Some(DntValue::Text(text.to_string())),
+        DntType::Bool if number.eq_ignore_ascii_case("true") => Some(DntValue::Int(1)),
+        DntType::Bool if number.eq_ignore_ascii_case("false") => Some(DntValue::Int(0)),
+        DntType::Bool | DntType::Int => number.parse().ok().map(DntValue::Int),
+        DntType::Float | DntType::PercentFloat => number.parse().ok().map(DntValue::Float),
+        DntType::Double => number.parse().ok().map(DntValue::Double),
+    }
+}
+
+/// Writes a .dnt one row at a time; the row count in the header is filled in by `finish`.
+pub struct DntWriter<W: Write + Seek> {
+    writer: W,
+    columns: Vec<DntColumn>,
+    rows_written: u32,
+    encoding: &'static Encoding,
+}
+
+const ROW_COUNT_OFFSET: u64 = 6;
+
+impl DntWriter<BufWriter<File>> {
+    /// Creates the output file, refusing columns that do not match the table's entry in `schemas`.
+    pub fn create(output_file: &str, columns: Vec<DntColumn>, encoding: &'static Encoding, schemas: &SchemaRegistry) -> io::Result<Self> {
+        for column in &columns {
+            column.data_type()?;
+        }
+        schemas.check_columns(output_file, &columns)?;
+        DntWriter::new(BufWriter::new(File::create(output_file)?), columns, encoding)
+    }
+}
+
+impl<W: Write + Seek> DntWriter<W> {
+    pub fn new(mut writer: W, columns: Vec<DntColumn>, encoding: &'static Encoding) -> io::Result<Self> {
+        for column in &columns {
+            column.data_type()?;
+        }
+
+        writer.write_all(&[0; 4])?;
+        writer.write_u16::<LittleEndian>(columns.len() as u16)?;
+        writer.write_u32::<LittleEndian>(0)?;
+
+        for column in &columns {
+            let name_bytes = encode_text(&column.name, encoding)?;
+            writer.write_u16::<LittleEndian>(name_bytes.len() as u16)?;
+            writer.write_all(&name_bytes)?;
+            writer.write_u8(column.type_byte)?;
+        }
+
+        Ok(DntWriter { writer, columns, rows_written: 0, encoding })
+    }
+
+    pub fn write_row(&mut self, row: &DntRow) -> io::Result<()> {
+        if row.values.len() != self.columns.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Row {} has {} values, expected {}", row.id, row.values.len(), self.columns.len()),
+            ));
+        }
+
+        self.writer.write_u32::<LittleEndian>(row.id)?;
+        for (column, value) in self.columns.iter().zip(&row.values) {
+            write_value(&mut self.writer, column, value, self.encoding)?;
         }
+        self.rows_written += 1;
+        Ok(())
     }
 
-    fs.write_all(&[5])?;
-    fs.write_all(b"THEND")?;
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.write_all(&[5])?;
+        self.writer.write_all(b"THEND")?;
+
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(ROW_COUNT_OFFSET))?;
+        self.writer.write_u32::<LittleEndian>(self.rows_written)?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+pub fn write_dnt(table: &DntTable, output_file: &str, encoding: &'static Encoding, schemas: &SchemaRegistry) -> io::Result<()> {
+    let mut writer = DntWriter::create(output_file, table.columns.clone(), encoding, schemas)?;
+    for row in &table.rows {
+        writer.write_row(row)?;
+    }
+    writer.finish()?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+fn write_value<W: Write>(writer: &mut W, column: &DntColumn, value: &DntValue, encoding: &'static Encoding) -> io::Result<()> {
+    match (column.data_type()?, value) {
+        (DntType::String, DntValue::Text(v)) => {
+            let bytes = encode_text(v, encoding)?;
+            writer.write_u16::<LittleEndian>(bytes.len() as u16)?;
+            writer.write_all(&bytes)?;
+        }
+        (DntType::Bool | DntType::Int, DntValue::Int(v)) => writer.write_i32::<LittleEndian>(*v)?,
+        (DntType::Float | DntType::PercentFloat, DntValue::Float(v)) => writer.write_f32::<LittleEndian>(*v)?,
+        (DntType::Double, DntValue::Double(v)) => writer.write_f64::<LittleEndian>(*v)?,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Value {:?} does not match type {} of column {}", value, column.type_byte, column.name),
+            ));
+        }
+    }
+    Ok(())
+}
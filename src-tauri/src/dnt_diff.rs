@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use encoding_rs::Encoding;
+use indexmap::IndexMap;
+use serde::Serialize;
+use crate::dnt_converter::{self, DntRow, DntTable, DntValue};
+
+#[derive(Serialize)]
+pub struct CellChange {
+    pub column: String,
+    pub old: DntValue,
+    pub new: DntValue,
+}
+
+#[derive(Serialize)]
+pub struct RowChange {
+    pub row_id: u32,
+    pub cells: Vec<CellChange>,
+}
+
+#[derive(Serialize)]
+pub struct AddedOrRemovedRow {
+    pub row_id: u32,
+    pub values: IndexMap<String, DntValue>,
+}
+
+#[derive(Serialize, Default)]
+pub struct DntDiff {
+    pub added_columns: Vec<String>,
+    pub removed_columns: Vec<String>,
+    pub added_rows: Vec<AddedOrRemovedRow>,
+    pub removed_rows: Vec<AddedOrRemovedRow>,
+    pub changed_rows: Vec<RowChange>,
+}
+
+impl DntDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_columns.is_empty()
+            && self.removed_columns.is_empty()
+            && self.added_rows.is_empty()
+            && self.removed_rows.is_empty()
+            && self.changed_rows.is_empty()
+    }
+}
+
+fn row_values(table: &DntTable, row: &DntRow) -> IndexMap<String, DntValue> {
+    table.columns.iter()
+        .zip(&row.values)
+        .map(|(column, value)| (column.name.clone(), value.clone()))
+        .collect()
+}
+
+pub fn diff_tables(old: &DntTable, new: &DntTable) -> DntDiff {
+    let mut diff = DntDiff::default();
+
+    let old_columns: HashMap<&str, usize> = old.columns.iter().enumerate().map(|(i, c)| (c.name.as_str(), i)).collect();
+    let new_columns: HashMap<&str, usize> = new.columns.iter().enumerate().map(|(i, c)| (c.name.as_str(), i)).collect();
+
+    diff.removed_columns = old.columns.iter()
+        .filter(|c| !new_columns.contains_key(c.name.as_str()))
+        .map(|c| c.name.clone())
+        .collect();
+    diff.added_columns = new.columns.iter()
+        .filter(|c| !old_columns.contains_key(c.name.as_str()))
+        .map(|c| c.name.clone())
+        .collect();
+
+    let shared_columns: Vec<(&str, usize, usize)> = old.columns.iter()
+        .filter_map(|c| new_columns.get(c.name.as_str()).map(|&n| (c.name.as_str(), old_columns[c.name.as_str()], n)))
+        .collect();
+
+    let old_rows: HashMap<u32, &DntRow> = old.rows.iter().map(|r| (r.id, r)).collect();
+    let new_rows: HashMap<u32, &DntRow> = new.rows.iter().map(|r| (r.id, r)).collect();
+
+    for row in &old.rows {
+        if !new_rows.contains_key(&row.id) {
+            diff.removed_rows.push(AddedOrRemovedRow { row_id: row.id, values: row_values(old, row) });
+        }
+    }
+
+    for row in &new.rows {
+        match old_rows.get(&row.id) {
+            None => diff.added_rows.push(AddedOrRemovedRow { row_id: row.id, values: row_values(new, row) }),
+            Some(old_row) => {
+                let cells: Vec<CellChange> = shared_columns.iter()
+                    .filter(|(_, o, n)| old_row.values[*o] != row.values[*n])
+                    .map(|(name, o, n)| CellChange {
+                        column: name.to_string(),
+                        old: old_row.values[*o].clone(),
+                        new: row.values[*n].clone(),
+                    })
+                    .collect();
+                if !cells.is_empty() {
+                    diff.changed_rows.push(RowChange { row_id: row.id, cells });
+                }
+            }
+        }
+    }
+
+    diff
+}
+
+pub fn write_text_report<W: Write>(diff: &DntDiff, output: &mut W) -> io::Result<()> {
+    if diff.is_empty() {
+        writeln!(output, "No differences")?;
+        return Ok(());
+    }
+
+    if !diff.added_columns.is_empty() {
+        writeln!(output, "Columns added: {}", diff.added_columns.join(", "))?;
+    }
+    if !diff.removed_columns.is_empty() {
+        writeln!(output, "Columns removed: {}", diff.removed_columns.join(", "))?;
+    }
+
+    writeln!(output, "Rows added: {}", diff.added_rows.len())?;
+    for row in &diff.added_rows {
+        let values: Vec<String> = row.values.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        writeln!(output, "  + {}\t{}", row.row_id, values.join("\t"))?;
+    }
+
+    writeln!(output, "Rows removed: {}", diff.removed_rows.len())?;
+    for row in &diff.removed_rows {
+        let values: Vec<String> = row.values.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        writeln!(output, "  - {}\t{}", row.row_id, values.join("\t"))?;
+    }
+
+    writeln!(output, "Rows changed: {}", diff.changed_rows.len())?;
+    for row in &diff.changed_rows {
+        writeln!(output, "  ~ {}", row.row_id)?;
+        for cell in &row.cells {
+            writeln!(output, "      {}: {:?} -> {:?}", cell.column, cell.old.to_string(), cell.new.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn diff_dnt(old_file: &str, new_file: &str, output_file: &str, encoding: &'static Encoding) -> io::Result<bool> {
+    let old = dnt_converter::read_dnt(old_file, encoding)?;
+    let new = dnt_converter::read_dnt(new_file, encoding)?;
+    let diff = diff_tables(&old, &new);
+
+    let mut output = BufWriter::new(File::create(output_file)?);
+    if output_file.to_lowercase().ends_with(".json") {
+        serde_json::to_writer_pretty(&mut output, &diff)?;
+    } else {
+        write_text_report(&diff, &mut output)?;
+    }
+    output.flush()?;
+
+    Ok(!diff.is_empty())
+}
@@ -0,0 +1,294 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use encoding_rs::Encoding;
+use crate::dnt_converter::{self, DntColumn, DntRow, DntTable, DntValue};
+use crate::dnt_schema::SchemaRegistry;
+
+pub struct MergeConflict {
+    pub row_id: u32,
+    pub column: Option<String>,
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+}
+
+struct Side<'a> {
+    columns: HashMap<&'a str, usize>,
+    rows: HashMap<u32, &'a DntRow>,
+}
+
+impl<'a> Side<'a> {
+    fn new(table: &'a DntTable) -> Self {
+        Side {
+            columns: table.columns.iter().enumerate().map(|(i, c)| (c.name.as_str(), i)).collect(),
+            rows: table.rows.iter().map(|r| (r.id, r)).collect(),
+        }
+    }
+
+    fn cell(&self, row_id: u32, column: &str) -> Option<&'a DntValue> {
+        let row = self.rows.get(&row_id)?;
+        self.columns.get(column).map(|&i| &row.values[i])
+    }
+
+    fn row_differs(&self, other: &Side, row_id: u32, columns: &[DntColumn]) -> bool {
+        columns.iter()
+            .filter(|c| self.columns.contains_key(c.name.as_str()) && other.columns.contains_key(c.name.as_str()))
+            .any(|c| self.cell(row_id, &c.name) != other.cell(row_id, &c.name))
+    }
+}
+
+fn describe(value: Option<&DntValue>) -> Option<String> {
+    value.map(|v| v.to_string())
+}
+
+fn merge_columns(base: &DntTable, ours: &DntTable, theirs: &DntTable) -> Vec<DntColumn> {
+    let base_names: HashSet<&str> = base.columns.iter().map(|c| c.name.as_str()).collect();
+    let ours_names: HashSet<&str> = ours.columns.iter().map(|c| c.name.as_str()).collect();
+    let theirs_names: HashSet<&str> = theirs.columns.iter().map(|c| c.name.as_str()).collect();
+
+    let mut columns: Vec<DntColumn> = ours.columns.iter()
+        .filter(|c| !base_names.contains(c.name.as_str()) || theirs_names.contains(c.name.as_str()))
+        .map(|c| DntColumn { name: c.name.clone(), type_byte: c.type_byte })
+        .collect();
+
+    columns.extend(theirs.columns.iter()
+        .filter(|c| !base_names.contains(c.name.as_str()) && !ours_names.contains(c.name.as_str()))
+        .map(|c| DntColumn { name: c.name.clone(), type_byte: c.type_byte }));
+
+    columns
+}
+
+pub fn merge_tables(base: &DntTable, ours: &DntTable, theirs: &DntTable) -> (DntTable, Vec<MergeConflict>) {
+    let columns = merge_columns(base, ours, theirs);
+    let base_side = Side::new(base);
+    let ours_side = Side::new(ours);
+    let theirs_side = Side::new(theirs);
+
+    let mut conflicts = Vec::new();
+    let mut rows = Vec::new();
+
+    let mut row_ids: Vec<u32> = ours.rows.iter().map(|r| r.id).collect();
+    row_ids.extend(theirs.rows.iter().map(|r| r.id).filter(|id| !ours_side.rows.contains_key(id)));
+
+    for row_id in row_ids {
+        let in_base = base_side.rows.contains_key(&row_id);
+        let in_ours = ours_side.rows.contains_key(&row_id);
+        let in_theirs = theirs_side.rows.contains_key(&row_id);
+
+        if in_base && in_ours != in_theirs {
+            // Deleted on one side: the delete wins unless the other side also edited the row.
+            let kept_side = if in_ours { &ours_side } else { &theirs_side };
+            if !kept_side.row_differs(&base_side, row_id, &columns) {
+                continue;
+            }
+            conflicts.push(MergeConflict {
+                row_id,
+                column: None,
+                base: Some("present".to_string()),
+                ours: Some(if in_ours { "modified" } else { "deleted" }.to_string()),
+                theirs: Some(if in_theirs { "modified" } else { "deleted" }.to_string()),
+            });
+            if !in_ours {
+                continue;
+            }
+        }
+
+        let mut values = Vec::with_capacity(columns.len());
+        for column in &columns {
+            let b = base_side.cell(row_id, &column.name);
+            let o = ours_side.cell(row_id, &column.name);
+            let t = theirs_side.cell(row_id, &column.name);
+
+            let merged = if o == t || t.is_none() {
+                o
+            } else if o.is_none() || o == b {
+                t
+            } else if t == b {
+                o
+            } else {
+                conflicts.push(MergeConflict {
+                    row_id,
+                    column: Some(column.name.clone()),
+                    base: describe(b),
+                    ours: describe(o),
+                    theirs: describe(t),
+                });
+                o
+            };
+
+            values.push(merged.cloned().unwrap_or_else(|| dnt_converter::default_value(column.type_byte)));
+        }
+
+        rows.push(DntRow { id: row_id, values });
+    }
+
+    (DntTable { columns, rows }, conflicts)
+}
+
+pub fn write_conflict_report<W: Write>(conflicts: &[MergeConflict], output: &mut W) -> io::Result<()> {
+    if conflicts.is_empty() {
+        writeln!(output, "No conflicts")?;
+        return Ok(());
+    }
+
+    writeln!(output, "Conflicts: {} (kept our value)", conflicts.len())?;
+    for conflict in conflicts {
+        writeln!(
+            output,
+            "  {}\t{}\tbase={:?}\tours={:?}\ttheirs={:?}",
+            conflict.row_id,
+            conflict.column.as_deref().unwrap_or("<row>"),
+            conflict.base.as_deref().unwrap_or("<missing>"),
+            conflict.ours.as_deref().unwrap_or("<missing>"),
+            conflict.theirs.as_deref().unwrap_or("<missing>"),
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn merge_dnt(base_file: &str, ours_file: &str, theirs_file: &str, output_file: &str, encoding: &'static Encoding, schemas: &SchemaRegistry) -> io::Result<usize> {
+    let base = dnt_converter::read_dnt(base_file, encoding)?;
+    let ours = dnt_converter::read_dnt(ours_file, encoding)?;
+    let theirs = dnt_converter::read_dnt(theirs_file, encoding)?;
+
+    for column in &ours.columns {
+        if let Some(other) = theirs.columns.iter().find(|c| c.name == column.name) {
+            if other.type_byte != column.type_byte {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Column {} has type {} in ours but {} in theirs", column.name, column.type_byte, other.type_byte),
+                ));
+            }
+        }
+    }
+
+    let (merged, conflicts) = merge_tables(&base, &ours, &theirs);
+    dnt_converter::write_dnt(&merged, output_file, encoding, schemas)?;
+
+    let mut report = BufWriter::new(File::create(format!("{}.conflicts.txt", output_file))?);
+    write_conflict_report(&conflicts, &mut report)?;
+    report.flush()?;
+
+    Ok(conflicts.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(columns: &[(&str, u8)], rows: &[(u32, &[i32])]) -> DntTable {
+        DntTable {
+            columns: columns.iter().map(|(name, type_byte)| DntColumn { name: name.to_string(), type_byte: *type_byte }).collect(),
+            rows: rows.iter()
+                .map(|(id, values)| DntRow { id: *id, values: values.iter().map(|v| DntValue::Int(*v)).collect() })
+                .collect(),
+        }
+    }
+
+    fn ids(table: &DntTable) -> Vec<u32> {
+        table.rows.iter().map(|r| r.id).collect()
+    }
+
+    fn values(table: &DntTable, row_id: u32) -> Vec<DntValue> {
+        table.rows.iter().find(|r| r.id == row_id).unwrap().values.clone()
+    }
+
+    const COLUMNS: &[(&str, u8)] = &[("_A", 3), ("_B", 3)];
+
+    #[test]
+    fn delete_wins_over_an_unchanged_row() {
+        let base = table(COLUMNS, &[(1, &[1, 1]), (2, &[2, 2])]);
+        let ours = table(COLUMNS, &[(2, &[2, 2])]);
+        let theirs = table(COLUMNS, &[(1, &[1, 1]), (2, &[2, 2])]);
+
+        let (merged, conflicts) = merge_tables(&base, &ours, &theirs);
+        assert_eq!(ids(&merged), vec![2]);
+        assert!(conflicts.is_empty());
+
+        let (merged, conflicts) = merge_tables(&base, &theirs, &ours);
+        assert_eq!(ids(&merged), vec![2]);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn delete_against_edit_is_a_row_conflict() {
+        let base = table(COLUMNS, &[(1, &[1, 1])]);
+        let deleted = table(COLUMNS, &[]);
+        let edited = table(COLUMNS, &[(1, &[1, 5])]);
+
+        // We deleted, they edited: the row stays deleted.
+        let (merged, conflicts) = merge_tables(&base, &deleted, &edited);
+        assert!(merged.rows.is_empty());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].row_id, 1);
+        assert_eq!(conflicts[0].column, None);
+        assert_eq!(conflicts[0].ours.as_deref(), Some("deleted"));
+        assert_eq!(conflicts[0].theirs.as_deref(), Some("modified"));
+
+        // We edited, they deleted: our edit is kept.
+        let (merged, conflicts) = merge_tables(&base, &edited, &deleted);
+        assert_eq!(values(&merged, 1), vec![DntValue::Int(1), DntValue::Int(5)]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].ours.as_deref(), Some("modified"));
+    }
+
+    #[test]
+    fn column_added_on_one_side_is_kept() {
+        let base = table(COLUMNS, &[(1, &[1, 1])]);
+        let ours = table(COLUMNS, &[(1, &[7, 1]), (2, &[2, 2])]);
+        let theirs = table(&[("_A", 3), ("_B", 3), ("_C", 3)], &[(1, &[1, 1, 9])]);
+
+        let (merged, conflicts) = merge_tables(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        let names: Vec<&str> = merged.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["_A", "_B", "_C"]);
+        assert_eq!(values(&merged, 1), vec![DntValue::Int(7), DntValue::Int(1), DntValue::Int(9)]);
+        // A row only we have gets the default for their new column.
+        assert_eq!(values(&merged, 2), vec![DntValue::Int(2), DntValue::Int(2), DntValue::Int(0)]);
+    }
+
+    #[test]
+    fn column_removed_on_one_side_is_dropped() {
+        let base = table(COLUMNS, &[(1, &[1, 1])]);
+        let ours = table(COLUMNS, &[(1, &[1, 1])]);
+        let theirs = table(&[("_A", 3)], &[(1, &[1])]);
+
+        let (merged, _) = merge_tables(&base, &ours, &theirs);
+        let names: Vec<&str> = merged.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["_A"]);
+        assert_eq!(values(&merged, 1), vec![DntValue::Int(1)]);
+    }
+
+    #[test]
+    fn cells_changed_on_both_sides() {
+        let base = table(COLUMNS, &[(1, &[1, 1]), (2, &[2, 2])]);
+        let ours = table(COLUMNS, &[(1, &[5, 1]), (2, &[3, 2])]);
+        let theirs = table(COLUMNS, &[(1, &[6, 8]), (2, &[3, 2])]);
+
+        let (merged, conflicts) = merge_tables(&base, &ours, &theirs);
+        // _A conflicts and keeps our value, _B takes their one-sided change.
+        assert_eq!(values(&merged, 1), vec![DntValue::Int(5), DntValue::Int(8)]);
+        // The same change on both sides is not a conflict.
+        assert_eq!(values(&merged, 2), vec![DntValue::Int(3), DntValue::Int(2)]);
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        assert_eq!((conflict.row_id, conflict.column.as_deref()), (1, Some("_A")));
+        assert_eq!(
+            (conflict.base.as_deref(), conflict.ours.as_deref(), conflict.theirs.as_deref()),
+            (Some("1"), Some("5"), Some("6"))
+        );
+    }
+
+    #[test]
+    fn rows_added_on_both_sides_are_merged() {
+        let base = table(COLUMNS, &[]);
+        let ours = table(COLUMNS, &[(1, &[1, 1])]);
+        let theirs = table(COLUMNS, &[(2, &[2, 2]), (1, &[1, 1])]);
+
+        let (merged, conflicts) = merge_tables(&base, &ours, &theirs);
+        assert_eq!(ids(&merged), vec![1, 2]);
+        assert!(conflicts.is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+use std::io;
+use encoding_rs::Encoding;
+use crate::dnt_converter::{self, DntColumn, DntRow, DntTable, DntValue};
+use crate::dnt_schema::SchemaRegistry;
+
+#[derive(Default)]
+pub struct MigrationSummary {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub retyped: Vec<String>,
+}
+
+fn convert_value(value: &DntValue, type_byte: u8) -> Option<DntValue> {
+    match (value, type_byte) {
+        (DntValue::Text(v), _) => dnt_converter::parse_value(type_byte, if v.is_empty() && type_byte != 1 { "0" } else { v }),
+        (_, 1) => Some(DntValue::Text(value.to_string())),
+        (DntValue::Int(v), 2 | 3) => Some(DntValue::Int(*v)),
+        (DntValue::Int(v), 4 | 5) => Some(DntValue::Float(*v as f32)),
+        (DntValue::Int(v), 6) => Some(DntValue::Double(*v as f64)),
+        (DntValue::Float(v), 2 | 3) if v.fract() == 0.0 => Some(DntValue::Int(*v as i32)),
+        (DntValue::Float(v), 4 | 5) => Some(DntValue::Float(*v)),
+        (DntValue::Float(v), 6) => Some(DntValue::Double(*v as f64)),
+        (DntValue::Double(v), 2 | 3) if v.fract() == 0.0 => Some(DntValue::Int(*v as i32)),
+        (DntValue::Double(v), 4 | 5) => Some(DntValue::Float(*v as f32)),
+        (DntValue::Double(v), 6) => Some(DntValue::Double(*v)),
+        _ => None,
+    }
+}
+
+pub fn migrate_table(table: &DntTable, target_columns: &[DntColumn]) -> io::Result<(DntTable, MigrationSummary)> {
+    let mut summary = MigrationSummary::default();
+
+    let sources: Vec<Option<usize>> = target_columns.iter()
+        .map(|target| table.columns.iter().position(|c| c.name == target.name))
+        .collect();
+
+    for (target, source) in target_columns.iter().zip(&sources) {
+        match source {
+            None => summary.added.push(target.name.clone()),
+            Some(index) if table.columns[*index].type_byte != target.type_byte => summary.retyped.push(format!(
+                "{} ({} -> {})", target.name, table.columns[*index].type_byte, target.type_byte
+            )),
+            _ => {}
+        }
+    }
+    summary.removed = table.columns.iter()
+        .filter(|c| !target_columns.iter().any(|t| t.name == c.name))
+        .map(|c| c.name.clone())
+        .collect();
+
+    let mut rows = Vec::with_capacity(table.rows.len());
+    for row in &table.rows {
+        let mut values = Vec::with_capacity(target_columns.len());
+        for (target, source) in target_columns.iter().zip(&sources) {
+            let value = match source {
+                Some(index) => convert_value(&row.values[*index], target.type_byte).ok_or_else(|| io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Row {}: cannot convert {} value \"{}\" to type {}", row.id, target.name, row.values[*index], target.type_byte),
+                ))?,
+                None => dnt_converter::default_value(target.type_byte),
+            };
+            values.push(value);
+        }
+        rows.push(DntRow { id: row.id, values });
+    }
+
+    let columns = target_columns.iter()
+        .map(|c| DntColumn { name: c.name.clone(), type_byte: c.type_byte })
+        .collect();
+
+    Ok((DntTable { columns, rows }, summary))
+}
+
+pub fn migrate_dnt(input_file: &str, target_file: &str, output_file: &str, encoding: &'static Encoding, schemas: &SchemaRegistry) -> io::Result<MigrationSummary> {
+    let table = dnt_converter::read_dnt(input_file, encoding)?;
+    let target_columns = dnt_converter::read_dnt_columns(target_file, encoding)?;
+
+    let (migrated, summary) = migrate_table(&table, &target_columns)?;
+    dnt_converter::write_dnt(&migrated, output_file, encoding, schemas)?;
+
+    Ok(summary)
+}
@@ -0,0 +1,124 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+use encoding_rs::Encoding;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::dnt_converter::{self, DntRow, DntTable, DntValue};
+use crate::dnt_schema::SchemaRegistry;
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PatchOperation {
+    Set { row: u32, column: String, value: Value },
+    Add { row: u32, #[serde(default)] values: IndexMap<String, Value> },
+    Delete { row: u32 },
+}
+
+#[derive(Deserialize)]
+pub struct DntPatch {
+    #[serde(default)]
+    pub operations: Vec<PatchOperation>,
+    #[serde(default)]
+    pub tables: IndexMap<String, Vec<PatchOperation>>,
+}
+
+impl DntPatch {
+    pub fn operations_for(&self, table_name: &str) -> Vec<&PatchOperation> {
+        let mut operations: Vec<&PatchOperation> = self.operations.iter().collect();
+        if let Some(table_operations) = self.tables.iter().find(|(name, _)| name.eq_ignore_ascii_case(table_name)).map(|(_, ops)| ops) {
+            operations.extend(table_operations);
+        }
+        operations
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub fn json_to_value(value: &Value, type_byte: u8) -> Option<DntValue> {
+    match value {
+        Value::String(text) => dnt_converter::parse_value(type_byte, text),
+        Value::Bool(flag) if matches!(type_byte, 2 | 3) => Some(DntValue::Int(*flag as i32)),
+        Value::Number(number) => match type_byte {
+            1 => Some(DntValue::Text(number.to_string())),
+            2 | 3 => number.as_i64().and_then(|n| i32::try_from(n).ok()).map(DntValue::Int),
+            4 | 5 => number.as_f64().map(|n| DntValue::Float(n as f32)),
+            6 => number.as_f64().map(DntValue::Double),
+            _ => None,
+        },
+        Value::Null => Some(dnt_converter::default_value(type_byte)),
+        _ => None,
+    }
+}
+
+fn column_index(table: &DntTable, name: &str) -> io::Result<usize> {
+    table.columns.iter()
+        .position(|c| c.name == name)
+        .ok_or_else(|| invalid(format!("Unknown column {}", name)))
+}
+
+fn converted_value(table: &DntTable, index: usize, row: u32, value: &Value) -> io::Result<DntValue> {
+    let column = &table.columns[index];
+    json_to_value(value, column.type_byte).ok_or_else(|| invalid(format!(
+        "Row {}: {} is not valid for column {} (type {})", row, value, column.name, column.type_byte
+    )))
+}
+
+pub fn apply_patch(table: &mut DntTable, operations: &[&PatchOperation]) -> io::Result<()> {
+    for operation in operations {
+        match operation {
+            PatchOperation::Set { row, column, value } => {
+                let index = column_index(table, column)?;
+                let value = converted_value(table, index, *row, value)?;
+                let target = table.rows.iter_mut()
+                    .find(|r| r.id == *row)
+                    .ok_or_else(|| invalid(format!("Row {} does not exist", row)))?;
+                target.values[index] = value;
+            }
+            PatchOperation::Add { row, values } => {
+                if table.rows.iter().any(|r| r.id == *row) {
+                    return Err(invalid(format!("Row {} already exists", row)));
+                }
+                let mut new_values: Vec<DntValue> = table.columns.iter()
+                    .map(|c| dnt_converter::default_value(c.type_byte))
+                    .collect();
+                for (name, value) in values {
+                    let index = column_index(table, name)?;
+                    new_values[index] = converted_value(table, index, *row, value)?;
+                }
+                let position = table.rows.iter().position(|r| r.id > *row).unwrap_or(table.rows.len());
+                table.rows.insert(position, DntRow { id: *row, values: new_values });
+            }
+            PatchOperation::Delete { row } => {
+                let position = table.rows.iter()
+                    .position(|r| r.id == *row)
+                    .ok_or_else(|| invalid(format!("Row {} does not exist", row)))?;
+                table.rows.remove(position);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn load_patch(patch_file: &str) -> io::Result<DntPatch> {
+    let reader = BufReader::new(File::open(patch_file)?);
+    serde_json::from_reader(reader).map_err(|e| invalid(format!("{}: {}", patch_file, e)))
+}
+
+pub fn patch_dnt(input_file: &str, patch: &DntPatch, output_file: &str, encoding: &'static Encoding, schemas: &SchemaRegistry) -> io::Result<bool> {
+    let table_name = Path::new(input_file).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let operations = patch.operations_for(&table_name);
+    if operations.is_empty() {
+        return Ok(false);
+    }
+
+    let mut table = dnt_converter::read_dnt(input_file, encoding)?;
+    apply_patch(&mut table, &operations)
+        .map_err(|e| invalid(format!("{}: {}", table_name, e)))?;
+    dnt_converter::write_dnt(&table, output_file, encoding, schemas)?;
+
+    Ok(true)
+}
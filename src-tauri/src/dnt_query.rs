@@ -0,0 +1,593 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use encoding_rs::Encoding;
+use indexmap::IndexMap;
+use serde::Serialize;
+use crate::dnt_converter::{self, DntTable, DntValue};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Text(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+    Star,
+}
+
+#[derive(Debug)]
+pub enum Expr {
+    Column(String),
+    Number(f64),
+    Text(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, &'static str, Box<Expr>),
+}
+
+#[derive(Debug, Default)]
+pub struct Query {
+    pub columns: Option<Vec<String>>,
+    pub filter: Option<Expr>,
+    pub order_by: Option<(String, bool)>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+enum Scalar {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+}
+
+const OPERATORS: [&str; 10] = ["==", "!=", "<=", ">=", "&&", "||", "<", ">", "=", "!"];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if c == ',' {
+            tokens.push(Token::Comma);
+            i += 1;
+        } else if c == '*' {
+            tokens.push(Token::Star);
+            i += 1;
+        } else if c == '"' || c == '\'' {
+            let start = i + 1;
+            let end = chars[start..].iter().position(|&ch| ch == c)
+                .map(|p| start + p)
+                .ok_or_else(|| format!("Unterminated string starting at {}", i + 1))?;
+            tokens.push(Token::Text(chars[start..end].iter().collect()));
+            i = end + 1;
+        } else if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let number: String = chars[start..i].iter().collect();
+            tokens.push(Token::Number(number.parse().map_err(|_| format!("Invalid number {}", number))?));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let op = OPERATORS.iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| format!("Unexpected character '{}' at {}", c, i + 1))?;
+            tokens.push(Token::Op(if *op == "=" { "==" } else { op }));
+            i += op.len();
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(name)) if name.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.peek_keyword(keyword) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("Expected {}", keyword))
+        }
+    }
+
+    fn at_clause(&self) -> bool {
+        self.peek().is_none() || ["WHERE", "ORDER", "LIMIT"].iter().any(|k| self.peek_keyword(k))
+    }
+
+    fn column_name(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(name),
+            other => Err(format!("Expected column name, found {:?}", other)),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Op("||")) || self.peek_keyword("OR") {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_not()?;
+        while self.peek() == Some(&Token::Op("&&")) || self.peek_keyword("AND") {
+            self.pos += 1;
+            left = Expr::And(Box::new(left), Box::new(self.parse_not()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Op("!")) || self.peek_keyword("NOT") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_compare()
+    }
+
+    fn parse_compare(&mut self) -> Result<Expr, String> {
+        let left = self.parse_operand()?;
+        if let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            if ["==", "!=", "<", "<=", ">", ">="].contains(&op) {
+                self.pos += 1;
+                let right = self.parse_operand()?;
+                return Ok(Expr::Compare(Box::new(left), op, Box::new(right)));
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_operand(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(Expr::Column(name)),
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Text(t)) => Ok(Expr::Text(t)),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("Expected )".to_string()),
+                }
+            }
+            other => Err(format!("Unexpected {:?}", other)),
+        }
+    }
+}
+
+pub fn parse_query(text: &str) -> Result<Query, String> {
+    let mut parser = Parser { tokens: tokenize(text)?, pos: 0 };
+    let mut query = Query::default();
+
+    if parser.peek_keyword("SELECT") {
+        parser.pos += 1;
+        if parser.peek() == Some(&Token::Star) {
+            parser.pos += 1;
+        } else {
+            let mut columns = vec![parser.column_name()?];
+            while parser.peek() == Some(&Token::Comma) {
+                parser.pos += 1;
+                columns.push(parser.column_name()?);
+            }
+            query.columns = Some(columns);
+        }
+    }
+
+    if parser.peek_keyword("WHERE") {
+        parser.pos += 1;
+        query.filter = Some(parser.parse_or()?);
+    } else if !parser.at_clause() {
+        // A bare expression is shorthand for a WHERE clause.
+        query.filter = Some(parser.parse_or()?);
+    }
+
+    if parser.peek_keyword("ORDER") {
+        parser.pos += 1;
+        parser.expect_keyword("BY")?;
+        let column = parser.column_name()?;
+        let descending = if parser.peek_keyword("DESC") {
+            parser.pos += 1;
+            true
+        } else {
+            if parser.peek_keyword("ASC") {
+                parser.pos += 1;
+            }
+            false
+        };
+        query.order_by = Some((column, descending));
+    }
+
+    if parser.peek_keyword("LIMIT") {
+        parser.pos += 1;
+        match parser.next() {
+            Some(Token::Number(n)) if n >= 0.0 => query.limit = Some(n as usize),
+            other => return Err(format!("Expected row count after LIMIT, found {:?}", other)),
+        }
+    }
+
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected {:?}", token));
+    }
+
+    Ok(query)
+}
+
+/// A cell of a query result. `_RowID` is unsigned, unlike the i32 int columns, so it has its own variant.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum QueryValue {
+    RowId(u32),
+    Value(DntValue),
+}
+
+impl fmt::Display for QueryValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QueryValue::RowId(id) => write!(f, "{}", id),
+            QueryValue::Value(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+fn scalar(value: &QueryValue) -> Scalar {
+    match value {
+        QueryValue::RowId(id) => Scalar::Number(*id as f64),
+        QueryValue::Value(DntValue::Text(v)) => Scalar::Text(v.clone()),
+        QueryValue::Value(DntValue::Int(v)) => Scalar::Number(*v as f64),
+        // Compare the value users see in the exports: 0.1f32 is 0.1, not 0.10000000149011612.
+        QueryValue::Value(DntValue::Float(v)) => Scalar::Number(v.to_string().parse().unwrap_or(*v as f64)),
+        QueryValue::Value(DntValue::Double(v)) => Scalar::Number(*v),
+    }
+}
+
+fn compare_scalars(left: &Scalar, right: &Scalar) -> Option<Ordering> {
+    match (left, right) {
+        (Scalar::Number(a), Scalar::Number(b)) => a.partial_cmp(b),
+        (Scalar::Text(a), Scalar::Text(b)) => Some(a.cmp(b)),
+        (Scalar::Number(a), Scalar::Text(b)) => b.parse::<f64>().ok().and_then(|b| a.partial_cmp(&b)),
+        (Scalar::Text(a), Scalar::Number(b)) => a.parse::<f64>().ok().and_then(|a| a.partial_cmp(b)),
+        (Scalar::Bool(a), Scalar::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn is_nan(value: &Scalar) -> bool {
+    matches!(value, Scalar::Number(n) if n.is_nan())
+}
+
+/// A total order for ORDER BY, which `compare_scalars` is not: NaN is handled by the caller and
+/// values of different kinds, which never share a column, sort numbers before text before bools.
+fn sort_order(left: &Scalar, right: &Scalar) -> Ordering {
+    let kind = |value: &Scalar| match value {
+        Scalar::Number(_) => 0,
+        Scalar::Text(_) => 1,
+        Scalar::Bool(_) => 2,
+    };
+    match (left, right) {
+        (Scalar::Number(a), Scalar::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+        (Scalar::Text(a), Scalar::Text(b)) => a.cmp(b),
+        (Scalar::Bool(a), Scalar::Bool(b)) => a.cmp(b),
+        _ => kind(left).cmp(&kind(right)),
+    }
+}
+
+fn truthy(value: &Scalar) -> bool {
+    match value {
+        Scalar::Bool(b) => *b,
+        Scalar::Number(n) => *n != 0.0,
+        Scalar::Text(t) => !t.is_empty(),
+    }
+}
+
+fn evaluate(expr: &Expr, lookup: &dyn Fn(&str) -> Option<Scalar>) -> Result<Scalar, String> {
+    Ok(match expr {
+        Expr::Column(name) => lookup(name).ok_or_else(|| format!("Unknown column {}", name))?,
+        Expr::Number(n) => Scalar::Number(*n),
+        Expr::Text(t) => Scalar::Text(t.clone()),
+        Expr::Not(inner) => Scalar::Bool(!truthy(&evaluate(inner, lookup)?)),
+        Expr::And(a, b) => Scalar::Bool(truthy(&evaluate(a, lookup)?) && truthy(&evaluate(b, lookup)?)),
+        Expr::Or(a, b) => Scalar::Bool(truthy(&evaluate(a, lookup)?) || truthy(&evaluate(b, lookup)?)),
+        Expr::Compare(a, op, b) => {
+            let ordering = compare_scalars(&evaluate(a, lookup)?, &evaluate(b, lookup)?);
+            Scalar::Bool(match (*op, ordering) {
+                ("==", Some(o)) => o == Ordering::Equal,
+                ("!=", Some(o)) => o != Ordering::Equal,
+                ("!=", None) => true,
+                ("<", Some(o)) => o == Ordering::Less,
+                ("<=", Some(o)) => o != Ordering::Greater,
+                (">", Some(o)) => o == Ordering::Greater,
+                (">=", Some(o)) => o != Ordering::Less,
+                _ => false,
+            })
+        }
+    })
+}
+
+pub struct QueryResult {
+    pub columns: Vec<(String, u8)>,
+    pub rows: Vec<Vec<QueryValue>>,
+}
+
+pub fn run_query(table: &DntTable, query: &Query) -> Result<QueryResult, String> {
+    let mut all_columns: IndexMap<&str, (usize, u8)> = IndexMap::new();
+    all_columns.insert("_RowID", (0, 3));
+    for (index, column) in table.columns.iter().enumerate() {
+        all_columns.insert(column.name.as_str(), (index + 1, column.type_byte));
+    }
+
+    let full_row = |row_index: usize| -> Vec<QueryValue> {
+        let row = &table.rows[row_index];
+        let mut values = Vec::with_capacity(row.values.len() + 1);
+        values.push(QueryValue::RowId(row.id));
+        values.extend(row.values.iter().cloned().map(QueryValue::Value));
+        values
+    };
+
+    let mut matching: Vec<Vec<QueryValue>> = Vec::new();
+    for row_index in 0..table.rows.len() {
+        let values = full_row(row_index);
+        let keep = match &query.filter {
+            Some(filter) => {
+                let lookup = |name: &str| all_columns.get(name).map(|(i, _)| scalar(&values[*i]));
+                truthy(&evaluate(filter, &lookup)?)
+            }
+            None => true,
+        };
+        if keep {
+            matching.push(values);
+        }
+    }
+
+    if let Some((column, descending)) = &query.order_by {
+        let (index, _) = *all_columns.get(column.as_str()).ok_or_else(|| format!("Unknown column {}", column))?;
+        matching.sort_by(|a, b| {
+            let (a, b) = (scalar(&a[index]), scalar(&b[index]));
+            // NaN has no place among the numbers, so it goes last in either direction.
+            is_nan(&a).cmp(&is_nan(&b)).then_with(|| {
+                let ordering = sort_order(&a, &b);
+                if *descending { ordering.reverse() } else { ordering }
+            })
+        });
+    }
+
+    if let Some(limit) = query.limit {
+        matching.truncate(limit);
+    }
+
+    let selected: Vec<(&str, usize, u8)> = match &query.columns {
+        Some(names) => names.iter()
+            .map(|name| all_columns.get_full(name.as_str())
+                .map(|(_, key, (i, t))| (*key, *i, *t))
+                .ok_or_else(|| format!("Unknown column {}", name)))
+            .collect::<Result<_, _>>()?,
+        None => all_columns.iter().map(|(name, (i, t))| (*name, *i, *t)).collect(),
+    };
+
+    Ok(QueryResult {
+        columns: selected.iter().map(|(name, _, t)| (name.to_string(), *t)).collect(),
+        rows: matching.into_iter()
+            .map(|values| selected.iter().map(|(_, i, _)| values[*i].clone()).collect())
+            .collect(),
+    })
+}
+
+pub fn write_result<W: Write>(result: &QueryResult, json: bool, output: &mut W) -> io::Result<()> {
+    if json {
+        let rows: Vec<IndexMap<&str, &QueryValue>> = result.rows.iter()
+            .map(|values| result.columns.iter().map(|(name, _)| name.as_str()).zip(values).collect())
+            .collect();
+        serde_json::to_writer_pretty(&mut *output, &rows)?;
+        return Ok(());
+    }
+
+    let headers: Vec<String> = result.columns.iter()
+        .map(|(name, type_byte)| format!("{}|{}", name, type_byte))
+        .collect();
+    writeln!(output, "{}", headers.join("\t"))?;
+    for values in &result.rows {
+        let cells: Vec<String> = values.iter().map(|v| v.to_string().replace(",", "^")).collect();
+        writeln!(output, "{}", cells.join("\t"))?;
+    }
+    Ok(())
+}
+
+pub fn query_dnt(input_file: &str, query_text: &str, output_file: &str, encoding: &'static Encoding) -> io::Result<usize> {
+    let query = parse_query(query_text).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let table = dnt_converter::read_dnt(input_file, encoding)?;
+    let result = run_query(&table, &query).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let mut output = BufWriter::new(File::create(output_file)?);
+    write_result(&result, output_file.to_lowercase().ends_with(".json"), &mut output)?;
+    output.flush()?;
+
+    Ok(result.rows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dnt_converter::{DntColumn, DntRow};
+
+    fn table() -> DntTable {
+        let columns = [("_Name", 1), ("_A", 3), ("_B", 3)]
+            .iter()
+            .map(|(name, type_byte)| DntColumn { name: name.to_string(), type_byte: *type_byte })
+            .collect();
+        let rows = [(1, "5", 1, 0), (2, "abc", 2, 0), (3, "", 2, 3), (4, "5.0", 4, 3)]
+            .iter()
+            .map(|(id, name, a, b)| DntRow {
+                id: *id,
+                values: vec![DntValue::Text(name.to_string()), DntValue::Int(*a), DntValue::Int(*b)],
+            })
+            .collect();
+        DntTable { columns, rows }
+    }
+
+    fn float_table(rates: &[f32]) -> DntTable {
+        DntTable {
+            columns: vec![DntColumn { name: "_Rate".to_string(), type_byte: 4 }],
+            rows: rates.iter().enumerate()
+                .map(|(index, rate)| DntRow { id: index as u32 + 1, values: vec![DntValue::Float(*rate)] })
+                .collect(),
+        }
+    }
+
+    fn query_row_ids(table: &DntTable, text: &str) -> Vec<u32> {
+        let query = parse_query(text).unwrap();
+        let result = run_query(table, &query).unwrap();
+        let id_index = result.columns.iter().position(|(name, _)| name == "_RowID").unwrap();
+        result.rows.iter()
+            .map(|values| match values[id_index] {
+                QueryValue::RowId(id) => id,
+                ref other => panic!("unexpected _RowID {:?}", other),
+            })
+            .collect()
+    }
+
+    fn row_ids(text: &str) -> Vec<u32> {
+        query_row_ids(&table(), text)
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(row_ids("_A == 1 || _A == 2 && _B == 3"), vec![1, 3]);
+        assert_eq!(row_ids("_A == 2 && _B == 3 || _A == 1"), vec![1, 3]);
+        assert_eq!(row_ids("(_A == 1 || _A == 2) && _B == 3"), vec![3]);
+        assert_eq!(row_ids("_A = 1 OR _A = 2 AND _B = 3"), vec![1, 3]);
+    }
+
+    #[test]
+    fn not_applies_to_the_whole_comparison() {
+        assert_eq!(row_ids("!_A == 2"), vec![1, 4]);
+        assert_eq!(row_ids("NOT _A == 2 && _B == 3"), vec![4]);
+        assert_eq!(row_ids("!(_A == 2 && _B == 3)"), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn not_equal_with_mixed_types() {
+        // Text that reads as a number compares numerically; other text is never equal to a number.
+        assert_eq!(row_ids("_Name != 5"), vec![2, 3]);
+        assert_eq!(row_ids("_Name == 5"), vec![1, 4]);
+        assert_eq!(row_ids("_A != \"2\""), vec![1, 4]);
+        assert_eq!(row_ids("_A != 'abc'"), vec![1, 2, 3, 4]);
+        // Without an ordering, only != holds.
+        assert_eq!(row_ids("_A < 'abc' || _A >= 'abc'"), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn limit_applies_after_filter_and_order() {
+        assert_eq!(row_ids("SELECT * WHERE _A >= 2 ORDER BY _A DESC LIMIT 2"), vec![4, 2]);
+        assert_eq!(row_ids("ORDER BY _RowID DESC LIMIT 1"), vec![4]);
+        assert_eq!(row_ids("LIMIT 0"), Vec::<u32>::new());
+        assert_eq!(row_ids("_B == 3 LIMIT 10"), vec![3, 4]);
+    }
+
+    #[test]
+    fn invalid_limits_are_rejected() {
+        assert!(parse_query("LIMIT").is_err());
+        assert!(parse_query("LIMIT -1").is_err());
+        assert!(parse_query("LIMIT 'two'").is_err());
+        assert!(parse_query("LIMIT 2 3").is_err());
+    }
+
+    #[test]
+    fn selected_columns_and_unknown_names() {
+        let result = run_query(&table(), &parse_query("SELECT _B, _RowID WHERE _A == 4").unwrap()).unwrap();
+        assert_eq!(result.columns, vec![("_B".to_string(), 3), ("_RowID".to_string(), 3)]);
+        assert_eq!(result.rows, vec![vec![QueryValue::Value(DntValue::Int(3)), QueryValue::RowId(4)]]);
+
+        assert!(run_query(&table(), &parse_query("_Missing == 1").unwrap()).is_err());
+        assert!(run_query(&table(), &parse_query("ORDER BY _Missing").unwrap()).is_err());
+    }
+
+    #[test]
+    fn floats_compare_as_exported() {
+        let table = float_table(&[0.1, 0.2, 0.3, 1.0 / 3.0]);
+        assert_eq!(query_row_ids(&table, "_Rate == 0.1"), vec![1]);
+        assert_eq!(query_row_ids(&table, "_Rate <= 0.2"), vec![1, 2]);
+        assert_eq!(query_row_ids(&table, "_Rate >= 0.3"), vec![3, 4]);
+        assert_eq!(query_row_ids(&table, "_Rate == 0.33333334"), vec![4]);
+    }
+
+    #[test]
+    fn nan_sorts_last_without_panicking() {
+        let rates: Vec<f32> = (0..100)
+            .map(|i| match i % 7 {
+                0 => f32::NAN,
+                3 => -f32::NAN,
+                _ => (i * 37 % 101) as f32 / 10.0,
+            })
+            .collect();
+        let table = float_table(&rates);
+        for direction in ["ASC", "DESC"] {
+            let ids = query_row_ids(&table, &format!("ORDER BY _Rate {}", direction));
+            assert_eq!(ids.len(), 100);
+            let sorted: Vec<f32> = ids.iter().map(|id| rates[*id as usize - 1]).collect();
+            let numbers = sorted.iter().take_while(|rate| !rate.is_nan()).count();
+            assert_eq!(numbers, rates.iter().filter(|rate| !rate.is_nan()).count());
+            assert!(sorted[numbers..].iter().all(|rate| rate.is_nan()));
+            let in_order = sorted[..numbers].windows(2).all(|pair| if direction == "ASC" { pair[0] <= pair[1] } else { pair[0] >= pair[1] });
+            assert!(in_order, "{}", direction);
+        }
+        assert_eq!(query_row_ids(&table, "_Rate != _Rate").len(), rates.iter().filter(|rate| rate.is_nan()).count());
+    }
+
+    #[test]
+    fn large_row_ids_stay_unsigned() {
+        let mut table = float_table(&[1.0, 2.0]);
+        table.rows[1].id = 3_000_000_000;
+        assert_eq!(query_row_ids(&table, "_RowID > 2147483647"), vec![3_000_000_000]);
+        assert_eq!(query_row_ids(&table, "ORDER BY _RowID DESC"), vec![3_000_000_000, 1]);
+
+        let result = run_query(&table, &parse_query("SELECT _RowID WHERE _Rate == 2").unwrap()).unwrap();
+        let mut tsv = Vec::new();
+        write_result(&result, false, &mut tsv).unwrap();
+        assert_eq!(String::from_utf8(tsv).unwrap(), "_RowID|3\n3000000000\n");
+        let mut json = Vec::new();
+        write_result(&result, true, &mut json).unwrap();
+        assert!(String::from_utf8(json).unwrap().contains("\"_RowID\": 3000000000"));
+    }
+}
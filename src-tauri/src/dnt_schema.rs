@@ -0,0 +1,138 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use encoding_rs::Encoding;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use crate::dnt_converter::{self, DntColumn};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaColumn {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_byte: u8,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct SchemaRegistry {
+    pub tables: IndexMap<String, Vec<SchemaColumn>>,
+}
+
+pub fn table_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+pub fn load_registry(registry_file: &Path) -> io::Result<SchemaRegistry> {
+    let file = File::open(registry_file)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", registry_file.display(), e)))?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", registry_file.display(), e)))
+}
+
+impl SchemaRegistry {
+    pub fn validate(&self, table_name: &str, columns: &[DntColumn]) -> Vec<String> {
+        let expected = match self.tables.get(table_name) {
+            Some(expected) => expected,
+            None => return Vec::new(),
+        };
+
+        let mut problems = Vec::new();
+        for column in expected {
+            match columns.iter().find(|c| c.name == column.name) {
+                None => problems.push(format!("missing column {}", column.name)),
+                Some(actual) if actual.type_byte != column.type_byte => problems.push(format!(
+                    "column {} has type {}, expected {}", column.name, actual.type_byte, column.type_byte
+                )),
+                _ => {}
+            }
+        }
+        for column in columns {
+            if !expected.iter().any(|c| c.name == column.name) {
+                problems.push(format!("unexpected column {}", column.name));
+            }
+        }
+
+        // Names can repeat, so matching names alone do not mean matching lengths.
+        if problems.is_empty() && columns.len() != expected.len() {
+            problems.push(format!("has {} columns, expected {}", columns.len(), expected.len()));
+        }
+        if problems.is_empty() {
+            if let Some((index, (column, actual))) = expected.iter().zip(columns).enumerate().find(|(_, (c, a))| c.name != a.name) {
+                problems.push(format!(
+                    "column order differs: expected {} at position {}, found {}", column.name, index + 1, actual.name
+                ));
+            }
+        }
+
+        problems
+    }
+
+    pub fn check_columns(&self, output_file: &str, columns: &[DntColumn]) -> io::Result<()> {
+        let name = table_name(Path::new(output_file));
+        let problems = self.validate(&name, columns);
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} does not match the schema registry: {}", name, problems.join("; ")),
+            ))
+        }
+    }
+}
+
+pub fn build_registry(input_files: &[PathBuf], output_file: &str, encoding: &'static Encoding) -> io::Result<usize> {
+    // Building into an existing registry adds to it instead of starting over.
+    let mut registry = if Path::new(output_file).exists() {
+        load_registry(Path::new(output_file))?
+    } else {
+        SchemaRegistry::default()
+    };
+
+    for path in input_files {
+        let table = dnt_converter::read_dnt(path.to_str().unwrap(), encoding)?;
+        let columns = table.columns.iter()
+            .map(|c| SchemaColumn { name: c.name.clone(), type_byte: c.type_byte })
+            .collect();
+        registry.tables.insert(table_name(path), columns);
+    }
+
+    let mut output = BufWriter::new(File::create(output_file)?);
+    serde_json::to_writer_pretty(&mut output, &registry)?;
+    output.flush()?;
+
+    Ok(input_files.len())
+}
+
+pub fn validate_tables(input_files: &[PathBuf], registry: &SchemaRegistry, report_file: &str, encoding: &'static Encoding) -> io::Result<usize> {
+    let mut output = BufWriter::new(File::create(report_file)?);
+    let mut failed = 0;
+
+    for path in input_files {
+        let name = table_name(path);
+        if !registry.tables.contains_key(&name) {
+            writeln!(output, "{}: not in schema registry", name)?;
+            continue;
+        }
+
+        let problems = match dnt_converter::read_dnt(path.to_str().unwrap(), encoding) {
+            Ok(table) => registry.validate(&name, &table.columns),
+            Err(e) => vec![e.to_string()],
+        };
+
+        if problems.is_empty() {
+            writeln!(output, "{}: OK", name)?;
+        } else {
+            failed += 1;
+            for problem in problems {
+                writeln!(output, "{}: {}", name, problem)?;
+            }
+        }
+    }
+
+    output.flush()?;
+    Ok(failed)
+}
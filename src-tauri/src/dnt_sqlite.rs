@@ -0,0 +1,80 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use encoding_rs::Encoding;
+use rusqlite::{params_from_iter, Connection, ToSql};
+use crate::dnt_converter::{DntReader, DntValue};
+
+fn sql_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn column_affinity(type_byte: u8) -> &'static str {
+    match type_byte {
+        1 => "TEXT",
+        2 | 3 => "INTEGER",
+        _ => "REAL",
+    }
+}
+
+fn table_name(path: &Path) -> io::Result<String> {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid file name"))
+}
+
+pub fn export_to_sqlite(input_files: &[PathBuf], output_file: &str, encoding: &'static Encoding) -> io::Result<usize> {
+    if Path::new(output_file).exists() {
+        fs::remove_file(output_file)?;
+    }
+
+    let mut conn = Connection::open(output_file).map_err(sql_error)?;
+    let mut total_tables = 0;
+
+    for path in input_files {
+        let name = table_name(path)?;
+        let reader = DntReader::open(path.to_str().unwrap(), encoding)?;
+
+        let mut column_defs = vec!["\"_RowID\" INTEGER PRIMARY KEY".to_string()];
+        for column in reader.columns() {
+            column_defs.push(format!("{} {}", quote_identifier(&column.name), column_affinity(column.type_byte)));
+        }
+
+        let tx = conn.transaction().map_err(sql_error)?;
+        tx.execute(&format!("DROP TABLE IF EXISTS {}", quote_identifier(&name)), []).map_err(sql_error)?;
+        tx.execute(&format!("CREATE TABLE {} ({})", quote_identifier(&name), column_defs.join(", ")), []).map_err(sql_error)?;
+
+        {
+            let placeholders = vec!["?"; reader.columns().len() + 1].join(", ");
+            let mut stmt = tx
+                .prepare(&format!("INSERT INTO {} VALUES ({})", quote_identifier(&name), placeholders))
+                .map_err(sql_error)?;
+
+            for row in reader {
+                let row = row?;
+                let mut values: Vec<&dyn ToSql> = Vec::with_capacity(row.values.len() + 1);
+                values.push(&row.id);
+                for value in &row.values {
+                    values.push(match value {
+                        DntValue::Text(v) => v,
+                        DntValue::Int(v) => v,
+                        DntValue::Float(v) => v,
+                        DntValue::Double(v) => v,
+                    });
+                }
+                stmt.execute(params_from_iter(values)).map_err(|e| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("{}: row {}: {}", name, row.id, e))
+                })?;
+            }
+        }
+
+        tx.commit().map_err(sql_error)?;
+        total_tables += 1;
+    }
+
+    Ok(total_tables)
+}
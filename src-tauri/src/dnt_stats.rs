@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use encoding_rs::Encoding;
+use serde::Serialize;
+use crate::dnt_converter::{DntReader, DntValue, RowIdFindings};
+
+#[derive(Serialize)]
+pub struct ColumnStats {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_byte: u8,
+    pub distinct: usize,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub empty_ratio: Option<f64>,
+    pub constant: bool,
+}
+
+#[derive(Serialize)]
+pub struct TableStats {
+    pub row_count: usize,
+    pub duplicate_row_ids: Vec<u32>,
+    pub out_of_order_rows: usize,
+    pub constant_columns: Vec<String>,
+    pub columns: Vec<ColumnStats>,
+}
+
+impl TableStats {
+    /// Number of findings worth a look: duplicated IDs, unsorted rows and constant columns.
+    pub fn issue_count(&self) -> usize {
+        self.duplicate_row_ids.len() + (self.out_of_order_rows > 0) as usize + self.constant_columns.len()
+    }
+}
+
+#[derive(Default)]
+struct ColumnAccumulator {
+    distinct: HashSet<String>,
+    min: Option<f64>,
+    max: Option<f64>,
+    sum: f64,
+    empty: usize,
+}
+
+impl ColumnAccumulator {
+    fn add(&mut self, value: &DntValue) {
+        let number = match value {
+            DntValue::Text(v) => {
+                if v.is_empty() {
+                    self.empty += 1;
+                }
+                None
+            }
+            DntValue::Int(v) => Some(*v as f64),
+            DntValue::Float(v) => Some(*v as f64),
+            DntValue::Double(v) => Some(*v),
+        };
+        if let Some(number) = number {
+            self.min = Some(self.min.map_or(number, |min| min.min(number)));
+            self.max = Some(self.max.map_or(number, |max| max.max(number)));
+            self.sum += number;
+        }
+        self.distinct.insert(value.to_string());
+    }
+}
+
+pub fn table_stats(input_file: &str, encoding: &'static Encoding) -> io::Result<TableStats> {
+    let mut reader = DntReader::open(input_file, encoding)?.check_row_ids();
+    let mut accumulators: Vec<ColumnAccumulator> = reader.columns().iter().map(|_| ColumnAccumulator::default()).collect();
+    let mut row_count = 0;
+
+    for row in reader.by_ref() {
+        let row = row?;
+        row_count += 1;
+        for (accumulator, value) in accumulators.iter_mut().zip(&row.values) {
+            accumulator.add(value);
+        }
+    }
+
+    let columns: Vec<ColumnStats> = reader.columns().iter().zip(accumulators)
+        .map(|(column, accumulator)| {
+            let is_text = column.type_byte == 1;
+            ColumnStats {
+                name: column.name.clone(),
+                type_byte: column.type_byte,
+                distinct: accumulator.distinct.len(),
+                min: accumulator.min,
+                max: accumulator.max,
+                mean: accumulator.min.map(|_| accumulator.sum / row_count as f64),
+                empty_ratio: (is_text && row_count > 0).then(|| accumulator.empty as f64 / row_count as f64),
+                constant: row_count > 1 && accumulator.distinct.len() == 1,
+            }
+        })
+        .collect();
+
+    let constant_columns = columns.iter().filter(|c| c.constant).map(|c| c.name.clone()).collect();
+
+    let RowIdFindings { duplicate_row_ids, out_of_order_rows } = reader.row_id_findings().clone();
+
+    Ok(TableStats { row_count, duplicate_row_ids, out_of_order_rows, constant_columns, columns })
+}
+
+fn optional_number(value: Option<f64>) -> String {
+    value.map(|v| format!("{}", (v * 1000.0).round() / 1000.0)).unwrap_or_default()
+}
+
+pub fn write_text_report<W: Write>(stats: &TableStats, output: &mut W) -> io::Result<()> {
+    writeln!(output, "Rows: {}", stats.row_count)?;
+
+    let duplicates: Vec<String> = stats.duplicate_row_ids.iter().map(|id| id.to_string()).collect();
+    writeln!(output, "Duplicate _RowIDs: {}", if duplicates.is_empty() { "none".to_string() } else { duplicates.join(", ") })?;
+    writeln!(output, "Rows out of _RowID order: {}", stats.out_of_order_rows)?;
+    writeln!(output, "Constant columns: {}", if stats.constant_columns.is_empty() { "none".to_string() } else { stats.constant_columns.join(", ") })?;
+    writeln!(output)?;
+
+    writeln!(output, "column\ttype\tdistinct\tmin\tmax\tmean\tempty\tconstant")?;
+    for column in &stats.columns {
+        writeln!(
+            output,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            column.name,
+            column.type_byte,
+            column.distinct,
+            optional_number(column.min),
+            optional_number(column.max),
+            optional_number(column.mean),
+            column.empty_ratio.map(|r| format!("{:.1}%", r * 100.0)).unwrap_or_default(),
+            if column.constant { "yes" } else { "" },
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn stats_dnt(input_file: &str, output_file: &str, encoding: &'static Encoding) -> io::Result<usize> {
+    let stats = table_stats(input_file, encoding)?;
+
+    let mut output = BufWriter::new(File::create(output_file)?);
+    if output_file.to_lowercase().ends_with(".json") {
+        serde_json::to_writer_pretty(&mut output, &stats)?;
+    } else {
+        write_text_report(&stats, &mut output)?;
+    }
+    output.flush()?;
+
+    Ok(stats.issue_count())
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+use calamine::{open_workbook, Data, Reader, Xlsx};
+use encoding_rs::Encoding;
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use crate::dnt_converter::{self, DntColumn, DntRow, DntTable, DntValue};
+use crate::dnt_schema::SchemaRegistry;
+use crate::uistring::{self, UiStrings};
+
+fn xlsx_error(e: XlsxError) -> io::Error {
+    io::Error::other(e)
+}
+
+fn sheet_name(path: &Path) -> String {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let cleaned: String = stem
+        .chars()
+        .map(|c| if "[]:*?/\\".contains(c) { '_' } else { c })
+        .take(31)
+        .collect();
+    if cleaned.is_empty() { "Sheet1".to_string() } else { cleaned }
+}
+
+pub fn convert_to_xlsx(input_file: &str, output_file: &str, encoding: &'static Encoding, uistrings: Option<&UiStrings>) -> io::Result<()> {
+    let table = dnt_converter::read_dnt(input_file, encoding)?;
+
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_bold();
+    let text_format = Format::new().set_num_format("@");
+
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(sheet_name(Path::new(input_file))).map_err(xlsx_error)?;
+
+    // Excel column of each DNT column, leaving room for the uistring text next to string ID columns.
+    let mut excel_cols = Vec::with_capacity(table.columns.len());
+    let mut annotated = Vec::with_capacity(table.columns.len());
+    let mut next_col: u16 = 1;
+
+    worksheet.write_string_with_format(0, 0, "_RowID|3", &header_format).map_err(xlsx_error)?;
+    for column in &table.columns {
+        let header = format!("{}|{}", column.name, column.type_byte);
+        worksheet.write_string_with_format(0, next_col, header, &header_format).map_err(xlsx_error)?;
+        if column.type_byte == 1 {
+            worksheet.set_column_format(next_col, &text_format).map_err(xlsx_error)?;
+        }
+        excel_cols.push(next_col);
+        next_col += 1;
+
+        let is_annotated = uistrings.is_some() && matches!(column.type_byte, 2 | 3) && uistring::is_string_id_column(&column.name);
+        if is_annotated {
+            let header = format!("{}|1", uistring::annotation_column(&column.name));
+            worksheet.write_string_with_format(0, next_col, header, &header_format).map_err(xlsx_error)?;
+            next_col += 1;
+        }
+        annotated.push(is_annotated);
+    }
+    worksheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+
+    for (row_index, row) in table.rows.iter().enumerate() {
+        let excel_row = row_index as u32 + 1;
+        worksheet.write_number(excel_row, 0, row.id as f64).map_err(xlsx_error)?;
+
+        for (index, value) in row.values.iter().enumerate() {
+            let excel_col = excel_cols[index];
+            if let (true, Some(uistrings), DntValue::Int(id)) = (annotated[index], uistrings, value) {
+                if let Some(text) = u32::try_from(*id).ok().and_then(|id| uistrings.messages.get(&id)) {
+                    worksheet.write_string(excel_row, excel_col + 1, text).map_err(xlsx_error)?;
+                }
+            }
+            match value {
+                DntValue::Text(v) if v.is_empty() => {}
+                DntValue::Text(v) => {
+                    worksheet.write_string(excel_row, excel_col, v).map_err(xlsx_error)?;
+                }
+                DntValue::Int(v) => {
+                    worksheet.write_number(excel_row, excel_col, *v as f64).map_err(xlsx_error)?;
+                }
+                DntValue::Float(v) if !v.is_finite() => return Err(not_finite(excel_row, excel_col, v)),
+                DntValue::Double(v) if !v.is_finite() => return Err(not_finite(excel_row, excel_col, v)),
+                DntValue::Float(v) => {
+                    // Go through the shortest decimal form so 0.1f32 shows up as 0.1, not 0.100000001490116.
+                    let number = v.to_string().parse::<f64>().unwrap_or(*v as f64);
+                    worksheet.write_number(excel_row, excel_col, number).map_err(xlsx_error)?;
+                }
+                DntValue::Double(v) => {
+                    worksheet.write_number(excel_row, excel_col, *v).map_err(xlsx_error)?;
+                }
+            }
+        }
+    }
+
+    workbook.save(output_file).map_err(xlsx_error)?;
+
+    Ok(())
+}
+
+/// Excel has no NaN or infinity; such a cell would come back as `#NUM!` and fail the import.
+fn not_finite(row: u32, col: u16, value: impl fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: {} cannot be stored in a workbook", cell_reference(row as usize, col as usize), value),
+    )
+}
+
+fn cell_reference(row: usize, col: usize) -> String {
+    let mut letters = String::new();
+    let mut n = col + 1;
+    while n > 0 {
+        letters.insert(0, (b'A' + ((n - 1) % 26) as u8) as char);
+        n = (n - 1) / 26;
+    }
+    format!("{}{}", letters, row + 1)
+}
+
+fn cell_value(cell: &Data, type_byte: u8) -> Option<DntValue> {
+    match (type_byte, cell) {
+        (_, Data::Empty) => Some(dnt_converter::default_value(type_byte)),
+        (1, Data::String(v)) => Some(DntValue::Text(v.clone())),
+        (1, Data::Float(v)) => Some(DntValue::Text(v.to_string())),
+        (1, Data::Int(v)) => Some(DntValue::Text(v.to_string())),
+        (2 | 3, Data::Float(v)) if v.fract() == 0.0 && (i32::MIN as f64..=i32::MAX as f64).contains(v) => {
+            Some(DntValue::Int(*v as i32))
+        }
+        (2 | 3, Data::Int(v)) => i32::try_from(*v).ok().map(DntValue::Int),
+        (2 | 3, Data::Bool(v)) => Some(DntValue::Int(*v as i32)),
+        (4 | 5, Data::Float(v)) => Some(DntValue::Float(*v as f32)),
+        (4 | 5, Data::Int(v)) => Some(DntValue::Float(*v as f32)),
+        (6, Data::Float(v)) => Some(DntValue::Double(*v)),
+        (6, Data::Int(v)) => Some(DntValue::Double(*v as f64)),
+        (_, Data::String(v)) => dnt_converter::parse_value(type_byte, v),
+        _ => None,
+    }
+}
+
+/// `_RowID` is unsigned, so it gets the whole `u32` range instead of the `i32` one of its type.
+fn row_id(cell: &Data) -> Option<u32> {
+    match cell {
+        Data::Empty => Some(0),
+        Data::Float(v) if v.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(v) => Some(*v as u32),
+        Data::Int(v) => u32::try_from(*v).ok(),
+        Data::String(v) => v.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+pub fn convert_from_xlsx(input_file: &str, output_file: &str, encoding: &'static Encoding, schemas: &SchemaRegistry) -> io::Result<()> {
+    let mut workbook: Xlsx<_> = open_workbook(input_file)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", input_file, e)))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Workbook has no sheets"))?
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut rows = range.rows();
+    let header = rows
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Sheet has no header row"))?;
+
+    let mut types = Vec::with_capacity(header.len());
+    let mut columns = Vec::with_capacity(header.len().saturating_sub(1));
+    let mut annotations = Vec::new();
+    for (col, cell) in header.iter().enumerate() {
+        let text = cell.to_string();
+        let (name, type_byte) = text
+            .rsplit_once('|')
+            .and_then(|(name, type_byte)| Some((name, type_byte.trim().parse::<u8>().ok()?)))
+            .ok_or_else(|| io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: header \"{}\" is not in name|type form", cell_reference(0, col), text),
+            ))?;
+        types.push(type_byte);
+        if col > 0 && uistring::is_annotation_column(name) {
+            annotations.push(col);
+        } else if col > 0 {
+            columns.push(DntColumn { name: name.to_string(), type_byte });
+        }
+    }
+
+    if !matches!(types.first(), Some(2 | 3)) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "First column must be the integer _RowID"));
+    }
+
+    let mut table = DntTable { columns, rows: Vec::new() };
+
+    for (row_index, cells) in rows.enumerate() {
+        if cells.iter().all(|cell| *cell == Data::Empty) {
+            continue;
+        }
+
+        let invalid = |col: usize, cell: &Data| io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: \"{}\" is not valid for type {}", cell_reference(row_index + 1, col), cell, types[col]),
+        );
+        let id_cell = cells.first().unwrap_or(&Data::Empty);
+        let id = row_id(id_cell).ok_or_else(|| invalid(0, id_cell))?;
+
+        let mut values = Vec::with_capacity(types.len() - 1);
+        for (col, type_byte) in types.iter().enumerate().skip(1) {
+            if annotations.contains(&col) {
+                continue;
+            }
+            let cell = cells.get(col).unwrap_or(&Data::Empty);
+            values.push(cell_value(cell, *type_byte).ok_or_else(|| invalid(col, cell))?);
+        }
+
+        table.rows.push(DntRow { id, values });
+    }
+
+    dnt_converter::write_dnt(&table, output_file, encoding, schemas)
+}
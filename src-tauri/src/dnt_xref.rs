@@ -0,0 +1,198 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use encoding_rs::Encoding;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use crate::dnt_converter::{DntReader, DntRow, DntValue};
+use crate::dnt_schema;
+
+/// Table key whose columns apply to every table, e.g. `"*": { "_ItemID": "itemtable" }`.
+pub const ANY_TABLE: &str = "*";
+
+#[derive(Deserialize, Default)]
+#[serde(transparent)]
+pub struct ReferenceMap {
+    pub tables: IndexMap<String, IndexMap<String, String>>,
+}
+
+impl ReferenceMap {
+    /// Column -> referenced table for one table; table entries override the `*` ones.
+    pub fn references_for(&self, table_name: &str) -> IndexMap<&str, &str> {
+        let mut references = IndexMap::new();
+        if let Some(columns) = self.tables.get(ANY_TABLE) {
+            references.extend(columns.iter().map(|(c, t)| (c.as_str(), t.as_str())));
+        }
+        if let Some((_, columns)) = self.tables.iter().find(|(name, _)| name.eq_ignore_ascii_case(table_name)) {
+            references.extend(columns.iter().map(|(c, t)| (c.as_str(), t.as_str())));
+        }
+        references
+    }
+}
+
+pub fn load_reference_map(path: &str) -> io::Result<ReferenceMap> {
+    let file = File::open(path)?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e)))
+}
+
+/// IDs held by a cell. String cells may list several IDs separated by `,` or `;`; 0 means no reference.
+fn referenced_ids(value: &DntValue) -> Vec<i64> {
+    let ids = match value {
+        DntValue::Int(id) => vec![*id as i64],
+        DntValue::Text(text) => text.split([',', ';']).filter_map(|part| part.trim().parse().ok()).collect(),
+        _ => Vec::new(),
+    };
+    ids.into_iter().filter(|id| *id != 0).collect()
+}
+
+fn table_files(paths: &[PathBuf]) -> HashMap<String, PathBuf> {
+    paths.iter().map(|path| (dnt_schema::table_name(path), path.clone())).collect()
+}
+
+fn dnt_files_in(folder: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(folder)?.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("dnt")) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn find_row(path: &Path, row_id: u32, encoding: &'static Encoding) -> io::Result<Option<(Vec<String>, DntRow)>> {
+    let mut reader = DntReader::open(path.to_str().unwrap(), encoding)?;
+    for row in reader.by_ref() {
+        let row = row?;
+        if row.id == row_id {
+            let names = reader.columns().iter().map(|c| c.name.clone()).collect();
+            return Ok(Some((names, row)));
+        }
+    }
+    Ok(None)
+}
+
+fn write_row<W: Write>(output: &mut W, names: &[String], row: &DntRow, indent: &str) -> io::Result<()> {
+    for (name, value) in names.iter().zip(&row.values) {
+        writeln!(output, "{}{} = {}", indent, name, value)?;
+    }
+    Ok(())
+}
+
+/// Writes one row of `input_file` and every row it references in the .dnt files next to it.
+pub fn resolve_row(input_file: &str, references: &ReferenceMap, row_id: u32, output_file: &str, encoding: &'static Encoding) -> io::Result<usize> {
+    let input_path = Path::new(input_file);
+    let folder = input_path.parent().unwrap_or(Path::new("."));
+    let files = table_files(&dnt_files_in(folder)?);
+    let table_name = dnt_schema::table_name(input_path);
+
+    let (names, row) = find_row(input_path, row_id, encoding)?.ok_or_else(|| io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("Row {} not found in {}", row_id, table_name),
+    ))?;
+
+    let mut output = BufWriter::new(File::create(output_file)?);
+    writeln!(output, "{} {}", table_name, row_id)?;
+    write_row(&mut output, &names, &row, "  ")?;
+
+    let mut resolved = 0;
+    for (column, target) in references.references_for(&table_name) {
+        let value = match names.iter().position(|name| name == column) {
+            Some(index) => &row.values[index],
+            None => continue,
+        };
+
+        for id in referenced_ids(value) {
+            write!(output, "{} = {} -> ", column, id)?;
+            let target_row = match (files.get(&target.to_lowercase()), u32::try_from(id)) {
+                (Some(path), Ok(id)) => find_row(path, id, encoding)?,
+                (None, _) => {
+                    writeln!(output, "table {} not found", target)?;
+                    continue;
+                }
+                _ => None,
+            };
+            match target_row {
+                Some((target_names, target_row)) => {
+                    writeln!(output, "{} {}", target, id)?;
+                    write_row(&mut output, &target_names, &target_row, "    ")?;
+                    resolved += 1;
+                }
+                None => writeln!(output, "not found in {}", target)?,
+            }
+        }
+    }
+
+    output.flush()?;
+    Ok(resolved)
+}
+
+fn row_ids(path: &Path, encoding: &'static Encoding) -> io::Result<HashSet<u32>> {
+    let mut ids = HashSet::new();
+    for row in DntReader::open(path.to_str().unwrap(), encoding)? {
+        ids.insert(row?.id);
+    }
+    Ok(ids)
+}
+
+/// Reports every referenced ID that has no row in its target table; returns the number of dangling references.
+pub fn check_references(input_files: &[PathBuf], references: &ReferenceMap, report_file: &str, encoding: &'static Encoding) -> io::Result<usize> {
+    let files = table_files(input_files);
+    let mut id_sets: HashMap<String, Option<HashSet<u32>>> = HashMap::new();
+    let mut output = BufWriter::new(File::create(report_file)?);
+    let mut dangling = 0;
+
+    for path in input_files {
+        let table_name = dnt_schema::table_name(path);
+        let table_references = references.references_for(&table_name);
+        if table_references.is_empty() {
+            continue;
+        }
+
+        let mut reader = DntReader::open(path.to_str().unwrap(), encoding)?;
+        let checked: Vec<(usize, &str, String)> = table_references.iter()
+            .filter_map(|(column, target)| {
+                reader.columns().iter()
+                    .position(|c| c.name == *column)
+                    .map(|index| (index, *column, target.to_lowercase()))
+            })
+            .collect();
+
+        for (_, column, target) in &checked {
+            if !id_sets.contains_key(target) {
+                let ids = match files.get(target) {
+                    Some(target_path) => Some(row_ids(target_path, encoding)?),
+                    None => None,
+                };
+                if ids.is_none() {
+                    writeln!(output, "{}: {} references {}, which is not in the folder", table_name, column, target)?;
+                }
+                id_sets.insert(target.clone(), ids);
+            }
+        }
+
+        for row in reader.by_ref() {
+            let row = row?;
+            for (index, column, target) in &checked {
+                let ids = match &id_sets[target] {
+                    Some(ids) => ids,
+                    None => continue,
+                };
+                for id in referenced_ids(&row.values[*index]) {
+                    if !u32::try_from(id).is_ok_and(|id| ids.contains(&id)) {
+                        writeln!(output, "{} {}: {} = {} not found in {}", table_name, row.id, column, id, target)?;
+                        dangling += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    if dangling == 0 {
+        writeln!(output, "No dangling references")?;
+    }
+    output.flush()?;
+    Ok(dangling)
+}
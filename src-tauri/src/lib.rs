@@ -1,13 +1,98 @@
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 mod dnt_converter;
 mod act_converter;
+mod act_file;
+mod act_export;
+mod act_signal;
+mod act_lint;
 mod pak;
+mod dnt_sqlite;
+mod dnt_xlsx;
+mod dnt_diff;
+mod dnt_merge;
+mod dnt_patch;
+mod dnt_query;
+mod dnt_schema;
+mod dnt_migrate;
+mod dnt_xref;
+mod uistring;
+mod localization;
+mod dnt_stats;
+mod dnt_compile;
 
 use tauri_plugin_dialog::{MessageDialogKind, MessageDialogBuilder, DialogExt};
 use glob::glob;
 use std::path::{Path, PathBuf};
-use std::fs::{self, read_dir};
+use std::fs::{self, read_dir, File};
+use std::io::{self, BufWriter, Write};
 use std::time::Instant;
+use rayon::prelude::*;
+use serde::Deserialize;
+
+const SUMMARY_FILE: &str = "conversion_summary.txt";
+const MAX_LISTED_FAILURES: usize = 10;
+/// Folder modes whose outputs can land on the same path: every pak extracts into one shared tree
+/// where later patch paks must win, and recursive act files can share a name. These run one file
+/// at a time in input order; everything else writes one distinct output per input and runs in parallel.
+const SERIAL_MODES: [&str; 2] = ["Extract Pak", "Convert act v6 to v5"];
+
+enum FileOutcome {
+    Converted { count: usize, note: Option<String> },
+    Skipped(String),
+    Failed(String),
+}
+
+impl FileOutcome {
+    fn converted(count: usize) -> Self {
+        FileOutcome::Converted { count, note: None }
+    }
+}
+
+fn write_folder_summary(outcomes: &[(String, FileOutcome)], summary_file: &Path) -> io::Result<()> {
+    let mut output = BufWriter::new(File::create(summary_file)?);
+
+    writeln!(output, "Converted:")?;
+    for (name, outcome) in outcomes {
+        if let FileOutcome::Converted { .. } = outcome {
+            writeln!(output, "  {}", name)?;
+        }
+    }
+    writeln!(output, "Skipped:")?;
+    for (name, outcome) in outcomes {
+        if let FileOutcome::Skipped(reason) = outcome {
+            writeln!(output, "  {}: {}", name, reason)?;
+        }
+    }
+    writeln!(output, "Failed:")?;
+    for (name, outcome) in outcomes {
+        if let FileOutcome::Failed(reason) = outcome {
+            writeln!(output, "  {}: {}", name, reason)?;
+        }
+    }
+    output.flush()
+}
+
+fn summarize_outcomes(outcomes: &[(String, FileOutcome)]) -> String {
+    let converted = outcomes.iter().filter(|(_, o)| matches!(o, FileOutcome::Converted { .. })).count();
+    let skipped = outcomes.iter().filter(|(_, o)| matches!(o, FileOutcome::Skipped(_))).count();
+    let failures: Vec<String> = outcomes.iter()
+        .filter_map(|(name, outcome)| match outcome {
+            FileOutcome::Failed(reason) => Some(format!("{}: {}", name, reason)),
+            _ => None,
+        })
+        .collect();
+
+    let mut summary = format!("Converted: {}, skipped: {}, failed: {}", converted, skipped, failures.len());
+    for failure in failures.iter().take(MAX_LISTED_FAILURES) {
+        summary.push('\n');
+        summary.push_str(failure);
+    }
+    if failures.len() > MAX_LISTED_FAILURES {
+        summary.push_str(&format!("\n... and {} more", failures.len() - MAX_LISTED_FAILURES));
+    }
+    summary.push_str(&format!("\nSee {} for details", SUMMARY_FILE));
+    summary
+}
 
 fn get_all_act_files(dir: &Path) -> Vec<PathBuf> {
     let mut files = Vec::new();
@@ -26,69 +111,428 @@ fn get_all_act_files(dir: &Path) -> Vec<PathBuf> {
     files
 }
 
+/// The form fields of the converter page; each mode only reads the ones it needs.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ConvertOptions {
+    input_file: String,
+    output_file: String,
+    open_mode: String,
+    convert_mode: String,
+    encryption: bool,
+    compare_file: Option<String>,
+    base_file: Option<String>,
+    patch_file: Option<String>,
+    query: Option<String>,
+    strict: Option<bool>,
+    encoding: Option<String>,
+    reference_file: Option<String>,
+    row_id: Option<u32>,
+    uistring_file: Option<String>,
+    catalog_file: Option<String>,
+    sort_rows: Option<bool>,
+    act_version: Option<u32>,
+    signal_file: Option<String>,
+    schema_file: Option<String>,
+}
+
+/// Describes a successful run; `total` is whatever the mode counts (files, tables, rows or issues).
+fn result_message(convert_mode: &str, act_version: Option<u32>, total: usize, notes: &[String]) -> Option<String> {
+    let message = match convert_mode {
+        "Convert to .tsv" if notes.is_empty() => return None,
+        "Convert to .tsv" => {
+            let mut message = format!("{} tables have _RowID problems:", notes.len());
+            for note in notes.iter().take(MAX_LISTED_FAILURES) {
+                message.push('\n');
+                message.push_str(note);
+            }
+            if notes.len() > MAX_LISTED_FAILURES {
+                message.push_str(&format!("\n... and {} more", notes.len() - MAX_LISTED_FAILURES));
+            }
+            message
+        }
+        "Convert act v6 to v5" => format!(
+            "Converted {} act v6 to v5\n{} files dropped non-zero v6 fields (see *.dropped.txt)", total, notes.len()
+        ),
+        "Diff tables" => format!("Found differences in {} tables", total),
+        "Merge tables" => format!("Merged with {} conflicts (see .conflicts.txt)", total),
+        "Apply patch" => format!("Patched {} tables", total),
+        "Query table" => format!("Query matched {} rows", total),
+        "Build schema registry" => format!("Registered {} table schemas", total),
+        "Validate schema" => format!("{} tables do not match the schema registry", total),
+        "Migrate columns" => notes.join("\n"),
+        "Table statistics" => format!("Found {} _RowID problems or constant columns", total),
+        "Compile table source" => format!("Compiled {} rows", total),
+        "Export act" => format!("Exported {} actions", total),
+        "Import act" => format!("Imported {} actions", total),
+        "Convert act version" => format!(
+            "Converted to act v{}, dropping {} non-zero fields (see *.dropped.txt)", act_version.unwrap_or_default(), total
+        ),
+        "Extract strings" => format!("Extracted {} strings", total),
+        "Translate uistring" => format!("Translated {} uistring messages", total),
+        "Resolve references" => format!("Resolved {} references", total),
+        "Check references" => format!("Found {} dangling references", total),
+        "Lint act" => format!("Found {} act problems", total),
+        "Export to SQLite" => format!("Exported {} tables to SQLite", total),
+        _ => return None,
+    };
+    Some(message)
+}
+
 #[tauri::command(rename_all = "snake_case")]
-fn convert(app: tauri::AppHandle, input_file: String, output_file: String, open_mode: String, convert_mode: String, encryption: bool) {
+fn convert(app: tauri::AppHandle, options: ConvertOptions) {
+    let ConvertOptions {
+        input_file, output_file, open_mode, convert_mode, encryption, compare_file, base_file, patch_file, query, strict,
+        encoding, reference_file, row_id, uistring_file, catalog_file, sort_rows, act_version, signal_file,
+        schema_file,
+    } = options;
     let start = Instant::now();
-    let mut total_act_convert = 0;
+    let mut total = 0;
+    let mut notes: Vec<String> = Vec::new();
+    let compare_file = compare_file.unwrap_or_default();
+    let base_file = base_file.unwrap_or_default();
+    let query = query.unwrap_or_default();
+    let mut error_message: Option<String> = None;
+    let mut folder_summary: Option<String> = None;
+    let reference_file = reference_file.unwrap_or_default();
+    let encoding = dnt_converter::encoding_from_name(encoding.as_deref().unwrap_or("UTF-8")).unwrap_or_else(|e| {
+        error_message = Some(e.to_string());
+        encoding_rs::UTF_8
+    });
+    let catalog_file = catalog_file.unwrap_or_default();
+    let translations = if convert_mode == "Convert to .dnt" && !catalog_file.is_empty() {
+        match localization::load_catalog(&catalog_file) {
+            Ok(catalog) => Some(catalog),
+            Err(e) => {
+                error_message = Some(format!("Failed to load translations: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let tsv_options = dnt_converter::TsvImportOptions {
+        strict: strict.unwrap_or(false),
+        encoding,
+        translations,
+        sort_by_row_id: sort_rows.unwrap_or(false),
+    };
+    let uistrings = match uistring_file.as_deref() {
+        Some(path) if !path.is_empty() && ["Convert to .tsv", "Convert to .xlsx", "Extract strings"].contains(&convert_mode.as_str()) => {
+            match uistring::load_uistring(path) {
+                Ok(uistrings) => Some(uistrings),
+                Err(e) => {
+                    error_message = Some(format!("Failed to load uistring: {}", e));
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+    let signals = match signal_file.as_deref() {
+        Some(path) if !path.is_empty() && ["Export act", "Import act"].contains(&convert_mode.as_str()) => {
+            match act_signal::load_signal_definitions(path) {
+                Ok(signals) => Some(signals),
+                Err(e) => {
+                    error_message = Some(format!("Failed to load signal definitions: {}", e));
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+    let mut schema_warning: Option<String> = None;
+    let schemas = if ["Convert to .dnt", "Convert .xlsx to .dnt", "Apply patch", "Migrate columns", "Merge tables", "Compile table source", "Validate schema"].contains(&convert_mode.as_str()) {
+        match schema_file.as_deref() {
+            Some(path) if !path.is_empty() => match dnt_schema::load_registry(Path::new(path)) {
+                Ok(schemas) => {
+                    if schemas.tables.is_empty() {
+                        schema_warning = Some(format!("Schema registry {} has no tables, nothing was checked against it", path));
+                    }
+                    schemas
+                }
+                Err(e) => {
+                    error_message = Some(format!("Failed to load schema registry: {}", e));
+                    dnt_schema::SchemaRegistry::default()
+                }
+            },
+            _ if convert_mode == "Validate schema" => {
+                error_message = Some("No schema registry selected".to_string());
+                dnt_schema::SchemaRegistry::default()
+            }
+            _ => {
+                schema_warning = Some("No schema registry selected, tables were written without a schema check".to_string());
+                dnt_schema::SchemaRegistry::default()
+            }
+        }
+    } else {
+        dnt_schema::SchemaRegistry::default()
+    };
+    let patch = if convert_mode == "Apply patch" {
+        match dnt_patch::load_patch(patch_file.as_deref().unwrap_or_default()) {
+            Ok(patch) => Some(patch),
+            Err(e) => {
+                error_message = Some(format!("Failed to load patch: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    if error_message.is_some() {
+        // A catalog, registry or definition file the user asked for failed to load; converting without it
+        // would silently write untranslated, unchecked or undecoded output.
+    } else if ["Export to SQLite", "Build schema registry", "Validate schema", "Check references", "Extract strings", "Lint act"].contains(&convert_mode.as_str()) {
+        let input_files: Vec<PathBuf> = if open_mode == "Folder" {
+            glob(&input_file).expect("Failed to read glob pattern").flatten().collect()
+        } else {
+            vec![PathBuf::from(&input_file)]
+        };
 
-    if open_mode == "Folder" {
+        let result = match convert_mode.as_str() {
+            "Export to SQLite" => dnt_sqlite::export_to_sqlite(&input_files, output_file.as_str(), encoding),
+            "Build schema registry" => dnt_schema::build_registry(&input_files, output_file.as_str(), encoding),
+            "Check references" => dnt_xref::load_reference_map(&reference_file)
+                .and_then(|references| dnt_xref::check_references(&input_files, &references, output_file.as_str(), encoding)),
+            "Extract strings" => localization::extract_catalog(&input_files, uistrings.as_ref(), output_file.as_str(), encoding),
+            "Lint act" => act_lint::lint_acts(&input_files, output_file.as_str()),
+            _ => dnt_schema::validate_tables(&input_files, &schemas, output_file.as_str(), encoding),
+        };
+        match result {
+            Ok(count) => total = count,
+            Err(e) => error_message = Some(e.to_string()),
+        }
+    } else if open_mode == "Folder" {
         fs::create_dir_all(&output_file).expect("Failed to create output directory");
 
+        let mut input_paths = Vec::new();
+        let mut outcomes: Vec<(String, FileOutcome)> = Vec::new();
         if convert_mode == "Convert act v6 to v5" {
-            let input_path = Path::new(&input_file);
-            for path in get_all_act_files(input_path) {
-                let file_name = path.file_name().unwrap().to_str().unwrap();
-                let output_file_path = Path::new(&output_file).join(file_name);
-
-                if let Ok(is_v6) = act_converter::convert_act_v6_to_v5(path.to_str().unwrap(), output_file_path.to_str().unwrap()) {
-                    if is_v6 {
-                        total_act_convert += 1;
-                    }
-                }
-            }
+            input_paths = get_all_act_files(Path::new(&input_file));
         } else {
             for entry in glob(&input_file).expect("Failed to read glob pattern") {
                 match entry {
-                    Ok(path) => {
-                        let input_name = path.to_str().unwrap();
-                        let file_name = path.file_name().unwrap().to_str().unwrap();
-
-                        let output_file_path = if convert_mode == "Convert to .tsv" {
-                            Path::new(&output_file).join(file_name.replace(".dnt", ".tsv"))
-                        } else {
-                            Path::new(&output_file).join(file_name.replace(".tsv", ".dnt"))
-                        };
-
-                        match convert_mode.as_str() {
-                            "Convert to .tsv" => {
-                                let _ = dnt_converter::convert_to_tsv(input_name, output_file_path.to_str().unwrap());
-                            }
-                            "Convert to .dnt" => {
-                                let _ = dnt_converter::convert_to_dnt(input_name, output_file_path.to_str().unwrap());
-                            }
-                            "Extract Pak" => {
-                                let _ = pak::pak_extract(input_name, output_file_path.to_str().unwrap(),encryption);
-                            }
-                            _ => {}
-                        }
-                    }
-                    Err(_) => {}
+                    Ok(path) => input_paths.push(path),
+                    Err(e) => outcomes.push((e.path().display().to_string(), FileOutcome::Failed(e.error().to_string()))),
+                }
+            }
+        }
+
+        let convert_path = |path: &PathBuf| {
+            let input_name = path.to_str().unwrap();
+            let file_name = path.file_name().unwrap().to_str().unwrap();
+
+            let output_file_path = match convert_mode.as_str() {
+                "Convert to .tsv" => Path::new(&output_file).join(file_name.replace(".dnt", ".tsv")),
+                "Convert to .xlsx" => Path::new(&output_file).join(file_name.replace(".dnt", ".xlsx")),
+                "Convert .xlsx to .dnt" => Path::new(&output_file).join(file_name.replace(".xlsx", ".dnt")),
+                "Diff tables" => Path::new(&output_file).join(file_name.replace(".dnt", ".diff.txt")),
+                "Query table" => Path::new(&output_file).join(file_name.replace(".dnt", ".tsv")),
+                "Table statistics" => Path::new(&output_file).join(file_name.replace(".dnt", ".stats.txt")),
+                "Compile table source" => Path::new(&output_file).join(Path::new(file_name).with_extension("dnt")),
+                "Export act" => Path::new(&output_file).join(Path::new(file_name).with_extension("json")),
+                "Import act" => Path::new(&output_file).join(Path::new(file_name).with_extension("act")),
+                "Convert act version" => Path::new(&output_file).join(file_name),
+                _ => Path::new(&output_file).join(file_name.replace(".tsv", ".dnt")),
+            };
+            let output_name = output_file_path.to_str().unwrap();
+            let compare_path = Path::new(&compare_file).join(file_name);
+
+            let outcome = match convert_mode.as_str() {
+                "Convert to .tsv" => dnt_converter::convert_to_tsv(input_name, output_name, encoding, uistrings.as_ref())
+                    .map(|findings| FileOutcome::Converted {
+                        count: 1,
+                        note: (!findings.is_empty()).then(|| format!("{}: {}", file_name, findings)),
+                    }),
+                "Convert to .dnt" => dnt_converter::convert_to_dnt(input_name, output_name, &tsv_options, &schemas).map(|_| FileOutcome::converted(1)),
+                "Convert to .xlsx" => dnt_xlsx::convert_to_xlsx(input_name, output_name, encoding, uistrings.as_ref()).map(|_| FileOutcome::converted(1)),
+                "Convert .xlsx to .dnt" => dnt_xlsx::convert_from_xlsx(input_name, output_name, encoding, &schemas).map(|_| FileOutcome::converted(1)),
+                "Diff tables" | "Migrate columns" | "Merge tables" if !compare_path.exists() => {
+                    Ok(FileOutcome::Skipped(format!("{} not found", compare_path.display())))
+                }
+                "Diff tables" => dnt_diff::diff_dnt(input_name, compare_path.to_str().unwrap(), output_name, encoding)
+                    .map(|changed| FileOutcome::converted(changed as usize)),
+                "Query table" => dnt_query::query_dnt(input_name, query.as_str(), output_name, encoding).map(FileOutcome::converted),
+                "Table statistics" => dnt_stats::stats_dnt(input_name, output_name, encoding).map(FileOutcome::converted),
+                "Compile table source" => dnt_compile::compile_table(input_name, output_name, encoding, &schemas).map(FileOutcome::converted),
+                "Export act" => act_export::export_act(input_name, output_name, signals.as_ref()).map(FileOutcome::converted),
+                "Import act" => act_export::import_act(input_name, output_name, act_version, signals.as_ref()).map(FileOutcome::converted),
+                "Convert act version" => match act_version {
+                    Some(version) => act_converter::convert_act(input_name, output_name, version).map(|dropped| FileOutcome::converted(dropped.len())),
+                    None => Ok(FileOutcome::Failed("no target act version".to_string())),
+                },
+                "Apply patch" => match &patch {
+                    Some(patch) => dnt_patch::patch_dnt(input_name, patch, output_name, encoding, &schemas).map(|patched| if patched {
+                        FileOutcome::converted(1)
+                    } else {
+                        FileOutcome::Skipped("no patch operations for this table".to_string())
+                    }),
+                    None => Ok(FileOutcome::Failed("patch file could not be loaded".to_string())),
+                },
+                "Migrate columns" => dnt_migrate::migrate_dnt(input_name, compare_path.to_str().unwrap(), output_name, encoding, &schemas)
+                    .map(|summary| FileOutcome::Converted {
+                        count: 1,
+                        note: Some(format!("{}: +{} -{} ~{}", file_name, summary.added.len(), summary.removed.len(), summary.retyped.len())),
+                    }),
+                "Merge tables" => {
+                    let base_path = Path::new(&base_file).join(file_name);
+                    dnt_merge::merge_dnt(base_path.to_str().unwrap(), input_name, compare_path.to_str().unwrap(), output_name, encoding, &schemas)
+                        .map(FileOutcome::converted)
                 }
+                "Convert act v6 to v5" => act_converter::convert_act_v6_to_v5(input_name, output_name).map(|dropped| match dropped {
+                    Some(dropped) if !dropped.is_empty() => FileOutcome::Converted {
+                        count: 1,
+                        note: Some(format!("{}: dropped {} non-zero v6 fields", file_name, dropped.len())),
+                    },
+                    Some(_) => FileOutcome::converted(1),
+                    None => FileOutcome::Skipped("not an act v6 file".to_string()),
+                }),
+                "Extract Pak" => pak::pak_extract(input_name, output_name, encryption).map(|_| FileOutcome::converted(1)),
+                _ => Ok(FileOutcome::Skipped(format!("unsupported mode {}", convert_mode))),
+            };
+
+            (input_name.to_string(), outcome.unwrap_or_else(|e| FileOutcome::Failed(e.to_string())))
+        };
+        if SERIAL_MODES.contains(&convert_mode.as_str()) {
+            outcomes.extend(input_paths.iter().map(convert_path));
+        } else {
+            outcomes.par_extend(input_paths.par_iter().map(convert_path));
+        }
+
+        for (_, outcome) in &outcomes {
+            if let FileOutcome::Converted { count, note } = outcome {
+                total += count;
+                notes.extend(note.clone());
             }
         }
+
+        let summary_path = Path::new(&output_file).join(SUMMARY_FILE);
+        if let Err(e) = write_folder_summary(&outcomes, &summary_path) {
+            log::warn!("Failed to write {}: {}", summary_path.display(), e);
+        }
+        folder_summary = Some(summarize_outcomes(&outcomes));
     } else {
         match convert_mode.as_str() {
             "Convert to .tsv" => {
-                let _ = dnt_converter::convert_to_tsv(input_file.as_str(), output_file.as_str());
+                match dnt_converter::convert_to_tsv(input_file.as_str(), output_file.as_str(), encoding, uistrings.as_ref()) {
+                    Ok(findings) if !findings.is_empty() => notes.push(format!("{}: {}", input_file, findings)),
+                    Ok(_) => {}
+                    Err(e) => error_message = Some(e.to_string()),
+                }
             }
             "Convert to .dnt" => {
-                let _ = dnt_converter::convert_to_dnt(input_file.as_str(), output_file.as_str());
+                if let Err(e) = dnt_converter::convert_to_dnt(input_file.as_str(), output_file.as_str(), &tsv_options, &schemas) {
+                    error_message = Some(e.to_string());
+                }
+            }
+            "Convert to .xlsx" => {
+                if let Err(e) = dnt_xlsx::convert_to_xlsx(input_file.as_str(), output_file.as_str(), encoding, uistrings.as_ref()) {
+                    error_message = Some(e.to_string());
+                }
+            }
+            "Convert .xlsx to .dnt" => {
+                if let Err(e) = dnt_xlsx::convert_from_xlsx(input_file.as_str(), output_file.as_str(), encoding, &schemas) {
+                    error_message = Some(e.to_string());
+                }
+            }
+            "Diff tables" => {
+                match dnt_diff::diff_dnt(input_file.as_str(), compare_file.as_str(), output_file.as_str(), encoding) {
+                    Ok(changed) => total += changed as usize,
+                    Err(e) => error_message = Some(e.to_string()),
+                }
+            }
+            "Query table" => {
+                match dnt_query::query_dnt(input_file.as_str(), query.as_str(), output_file.as_str(), encoding) {
+                    Ok(rows) => total += rows,
+                    Err(e) => error_message = Some(e.to_string()),
+                }
+            }
+            "Apply patch" => {
+                if let Some(patch) = &patch {
+                    match dnt_patch::patch_dnt(input_file.as_str(), patch, output_file.as_str(), encoding, &schemas) {
+                        Ok(true) => total += 1,
+                        Ok(false) => {}
+                        Err(e) => error_message = Some(e.to_string()),
+                    }
+                }
+            }
+            "Migrate columns" => {
+                match dnt_migrate::migrate_dnt(input_file.as_str(), compare_file.as_str(), output_file.as_str(), encoding, &schemas) {
+                    Ok(summary) => {
+                        notes.push(format!("Added: {}", summary.added.join(", ")));
+                        notes.push(format!("Removed: {}", summary.removed.join(", ")));
+                        notes.push(format!("Retyped: {}", summary.retyped.join(", ")));
+                    }
+                    Err(e) => error_message = Some(e.to_string()),
+                }
+            }
+            "Merge tables" => {
+                match dnt_merge::merge_dnt(base_file.as_str(), input_file.as_str(), compare_file.as_str(), output_file.as_str(), encoding, &schemas) {
+                    Ok(conflicts) => total += conflicts,
+                    Err(e) => error_message = Some(e.to_string()),
+                }
+            }
+            "Table statistics" => {
+                match dnt_stats::stats_dnt(input_file.as_str(), output_file.as_str(), encoding) {
+                    Ok(issues) => total = issues,
+                    Err(e) => error_message = Some(e.to_string()),
+                }
+            }
+            "Compile table source" => {
+                match dnt_compile::compile_table(input_file.as_str(), output_file.as_str(), encoding, &schemas) {
+                    Ok(rows) => total = rows,
+                    Err(e) => error_message = Some(e.to_string()),
+                }
+            }
+            "Export act" => {
+                match act_export::export_act(input_file.as_str(), output_file.as_str(), signals.as_ref()) {
+                    Ok(actions) => total = actions,
+                    Err(e) => error_message = Some(e.to_string()),
+                }
+            }
+            "Import act" => {
+                match act_export::import_act(input_file.as_str(), output_file.as_str(), act_version, signals.as_ref()) {
+                    Ok(actions) => total = actions,
+                    Err(e) => error_message = Some(e.to_string()),
+                }
+            }
+            "Convert act version" => {
+                let result = act_version
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No target act version"))
+                    .and_then(|version| act_converter::convert_act(input_file.as_str(), output_file.as_str(), version));
+                match result {
+                    Ok(dropped) => total = dropped.len(),
+                    Err(e) => error_message = Some(e.to_string()),
+                }
+            }
+            "Translate uistring" => {
+                let result = localization::load_catalog(&catalog_file)
+                    .and_then(|catalog| localization::translate_uistring(input_file.as_str(), &catalog, output_file.as_str()));
+                match result {
+                    Ok(translated) => total = translated,
+                    Err(e) => error_message = Some(e.to_string()),
+                }
+            }
+            "Resolve references" => {
+                let result = dnt_xref::load_reference_map(&reference_file).and_then(|references| {
+                    dnt_xref::resolve_row(input_file.as_str(), &references, row_id.unwrap_or_default(), output_file.as_str(), encoding)
+                });
+                match result {
+                    Ok(resolved) => total = resolved,
+                    Err(e) => error_message = Some(e.to_string()),
+                }
             }
             "Convert act v6 to v5" => {
-                if let Ok(is_v6) = act_converter::convert_act_v6_to_v5(input_file.as_str(), output_file.as_str()) {
-                    if is_v6 {
-                        total_act_convert += 1;
+                match act_converter::convert_act_v6_to_v5(input_file.as_str(), output_file.as_str()) {
+                    Ok(Some(dropped)) => {
+                        total += 1;
+                        if !dropped.is_empty() {
+                            notes.push(format!("{}: dropped {} non-zero v6 fields", input_file, dropped.len()));
+                        }
                     }
+                    Ok(None) => {}
+                    Err(e) => error_message = Some(e.to_string()),
                 }
             }
             "Extract Pak" => {
@@ -99,22 +543,24 @@ fn convert(app: tauri::AppHandle, input_file: String, output_file: String, open_
     }
 
     let duration = start.elapsed();
-    
-    let message = if convert_mode == "Convert act v6 to v5" {
-        format!(
-            "Converted {} act v6 to v5\nElapsed time: {:.2} seconds",
-            total_act_convert, duration.as_secs_f32()
-        )
-    } else {
-        format!("Total time elapsed: {:.2} seconds", duration.as_secs_f32())
+
+    let mut message = match error_message.clone().or_else(|| result_message(&convert_mode, act_version, total, &notes)) {
+        Some(text) => format!("{}\nElapsed time: {:.2} seconds", text, duration.as_secs_f32()),
+        None => format!("Total time elapsed: {:.2} seconds", duration.as_secs_f32()),
     };
+    if let Some(summary) = &folder_summary {
+        message = format!("{}\n{}", summary, message);
+    }
+    if let (None, Some(warning)) = (&error_message, &schema_warning) {
+        message = format!("{}\n{}", warning, message);
+    }
 
     MessageDialogBuilder::new(
         app.dialog().clone(), 
         "ActConverter", 
         message
     )
-    .kind(MessageDialogKind::Info)
+    .kind(if error_message.is_some() { MessageDialogKind::Error } else { MessageDialogKind::Info })
     .show(move |_response| {});
 }
 
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use encoding_rs::Encoding;
+use crate::dnt_converter::{DntReader, DntRow, DntValue};
+use crate::dnt_schema;
+use crate::uistring::{self, UiStrings};
+
+/// Translations from a PO catalog, keyed by `msgctxt` (`table/row/column` or `uistring/mid`).
+#[derive(Default)]
+pub struct Catalog {
+    pub translations: HashMap<String, String>,
+}
+
+pub fn cell_context(table_name: &str, row_id: u32, column: &str) -> String {
+    format!("{}/{}/{}", table_name, row_id, column)
+}
+
+pub fn uistring_context(mid: u32) -> String {
+    format!("uistring/{}", mid)
+}
+
+fn po_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+fn po_unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+fn write_entry<W: Write>(output: &mut W, context: &str, text: &str) -> io::Result<()> {
+    writeln!(output, "msgctxt \"{}\"", po_escape(context))?;
+    writeln!(output, "msgid \"{}\"", po_escape(text))?;
+    writeln!(output, "msgstr \"\"")?;
+    writeln!(output)
+}
+
+/// Writes every non-empty string cell (and uistring message) to a PO catalog; returns the number of entries.
+pub fn extract_catalog(input_files: &[PathBuf], uistrings: Option<&UiStrings>, output_file: &str, encoding: &'static Encoding) -> io::Result<usize> {
+    let mut output = BufWriter::new(File::create(output_file)?);
+    let mut total_entries = 0;
+
+    writeln!(output, "msgid \"\"")?;
+    writeln!(output, "msgstr \"Content-Type: text/plain; charset=UTF-8\\n\"")?;
+    writeln!(output)?;
+
+    for path in input_files {
+        let table_name = dnt_schema::table_name(path);
+        let mut reader = DntReader::open(path.to_str().unwrap(), encoding)?;
+        let names: Vec<String> = reader.columns().iter().map(|c| c.name.clone()).collect();
+
+        for row in reader.by_ref() {
+            let row = row?;
+            for (name, value) in names.iter().zip(&row.values) {
+                if let DntValue::Text(text) = value {
+                    if !text.is_empty() {
+                        write_entry(&mut output, &cell_context(&table_name, row.id, name), text)?;
+                        total_entries += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(uistrings) = uistrings {
+        for (mid, text) in &uistrings.messages {
+            if !text.is_empty() {
+                write_entry(&mut output, &uistring_context(*mid), text)?;
+                total_entries += 1;
+            }
+        }
+    }
+
+    output.flush()?;
+    Ok(total_entries)
+}
+
+enum PoField {
+    Context,
+    Id,
+    Translation,
+    Other,
+}
+
+fn quoted(path: &str, line_number: usize, text: &str) -> io::Result<String> {
+    let text = text.trim();
+    if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+        Ok(po_unescape(&text[1..text.len() - 1]))
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}:{}: expected a quoted string, found {}", path, line_number, text),
+        ))
+    }
+}
+
+/// Loads the translated entries of a PO catalog; entries with an empty `msgstr` are left out.
+pub fn load_catalog(path: &str) -> io::Result<Catalog> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut catalog = Catalog::default();
+    let mut context = String::new();
+    let mut translation = String::new();
+    let mut field = PoField::Other;
+
+    let mut flush = |context: &mut String, translation: &mut String| {
+        let (context, translation) = (std::mem::take(context), std::mem::take(translation));
+        if !context.is_empty() && !translation.is_empty() {
+            catalog.translations.insert(context, translation);
+        }
+    };
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        let line_number = index + 1;
+
+        if let Some(rest) = line.strip_prefix("msgctxt ") {
+            flush(&mut context, &mut translation);
+            context = quoted(path, line_number, rest)?;
+            field = PoField::Context;
+        } else if let Some(rest) = line.strip_prefix("msgid ") {
+            quoted(path, line_number, rest)?;
+            field = PoField::Id;
+        } else if let Some(rest) = line.strip_prefix("msgstr ") {
+            translation = quoted(path, line_number, rest)?;
+            field = PoField::Translation;
+        } else if line.starts_with('"') {
+            let text = quoted(path, line_number, line)?;
+            match field {
+                PoField::Context => context.push_str(&text),
+                PoField::Translation => translation.push_str(&text),
+                PoField::Id | PoField::Other => {}
+            }
+        } else if line.is_empty() {
+            flush(&mut context, &mut translation);
+            field = PoField::Other;
+        }
+    }
+    flush(&mut context, &mut translation);
+
+    Ok(catalog)
+}
+
+impl Catalog {
+    /// Replaces the string cells of a row that have a translation.
+    pub fn translate_row(&self, table_name: &str, column_names: &[&str], row: &mut DntRow) {
+        for (name, value) in column_names.iter().zip(row.values.iter_mut()) {
+            if let DntValue::Text(text) = value {
+                if let Some(translation) = self.translations.get(&cell_context(table_name, row.id, name)) {
+                    *text = translation.clone();
+                }
+            }
+        }
+    }
+
+    /// Replaces translated uistring messages; returns how many were translated.
+    pub fn translate_uistrings(&self, uistrings: &mut UiStrings) -> usize {
+        let mut translated = 0;
+        for (mid, text) in uistrings.messages.iter_mut() {
+            if let Some(translation) = self.translations.get(&uistring_context(*mid)) {
+                *text = translation.clone();
+                translated += 1;
+            }
+        }
+        translated
+    }
+}
+
+pub fn translate_uistring(input_file: &str, catalog: &Catalog, output_file: &str) -> io::Result<usize> {
+    let mut uistrings = uistring::load_uistring(input_file)?;
+    let translated = catalog.translate_uistrings(&mut uistrings);
+    uistring::write_uistring(&uistrings, output_file)?;
+    Ok(translated)
+}
@@ -0,0 +1,89 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use indexmap::IndexMap;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+/// Suffix of the read-only text columns added next to string ID columns in exports.
+pub const ANNOTATION_SUFFIX: &str = "#uistring";
+
+#[derive(Default)]
+pub struct UiStrings {
+    pub messages: IndexMap<u32, String>,
+}
+
+fn xml_error(path: &str, e: quick_xml::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e))
+}
+
+pub fn load_uistring(path: &str) -> io::Result<UiStrings> {
+    let mut reader = Reader::from_reader(BufReader::new(File::open(path)?));
+    let mut strings = UiStrings::default();
+    let mut buf = Vec::new();
+    let mut current: Option<(u32, String)> = None;
+
+    loop {
+        match reader.read_event_into(&mut buf).map_err(|e| xml_error(path, e))? {
+            Event::Start(e) if e.name().as_ref() == b"message" => {
+                let mid = e.try_get_attribute("mid")
+                    .map_err(|e| xml_error(path, e))?
+                    .and_then(|attr| std::str::from_utf8(&attr.value).ok()?.trim().parse::<u32>().ok());
+                current = mid.map(|mid| (mid, String::new()));
+            }
+            Event::CData(text) => {
+                if let Some((_, message)) = current.as_mut() {
+                    message.push_str(&String::from_utf8_lossy(&text.into_inner()));
+                }
+            }
+            Event::Text(text) => {
+                if let Some((_, message)) = current.as_mut() {
+                    message.push_str(&text.unescape().map_err(|e| xml_error(path, e))?);
+                }
+            }
+            Event::End(e) if e.name().as_ref() == b"message" => {
+                if let Some((mid, message)) = current.take() {
+                    strings.messages.insert(mid, message);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(strings)
+}
+
+pub fn write_uistring(strings: &UiStrings, path: &str) -> io::Result<()> {
+    let mut output = BufWriter::new(File::create(path)?);
+    writeln!(output, "<?xml version=\"1.0\" encoding=\"utf-8\"?>")?;
+    writeln!(output, "<messages>")?;
+    for (mid, text) in &strings.messages {
+        // A CDATA section cannot contain "]]>", so split it across two sections.
+        writeln!(output, "<message mid=\"{}\"><![CDATA[{}]]></message>", mid, text.replace("]]>", "]]]]><![CDATA[>"))?;
+    }
+    writeln!(output, "</messages>")?;
+    output.flush()
+}
+
+impl UiStrings {
+    /// Text for a string ID, flattened to one line so it fits in a TSV cell.
+    pub fn display_text(&self, id: i32) -> String {
+        u32::try_from(id).ok()
+            .and_then(|id| self.messages.get(&id))
+            .map(|text| text.replace("\r\n", "\\n").replace(['\n', '\r'], "\\n").replace('\t', " "))
+            .unwrap_or_default()
+    }
+}
+
+pub fn is_string_id_column(name: &str) -> bool {
+    name.ends_with("NameID") || name.ends_with("DescriptionID")
+}
+
+pub fn annotation_column(name: &str) -> String {
+    format!("{}{}", name, ANNOTATION_SUFFIX)
+}
+
+pub fn is_annotation_column(name: &str) -> bool {
+    name.ends_with(ANNOTATION_SUFFIX)
+}